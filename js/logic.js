@@ -34,7 +34,11 @@ const selectFileWithName = (filename) => {
 }
 
 const treeClick = (filename) => {
-    document.querySelector(`input[value='${filename}']`).click()
+    const input = document.querySelector(`input[value='${filename}']`);
+    if (!input) {
+        return
+    }
+    input.click()
     iteratePathComponents(filename).forEach((pathComponent) => {
         const i = document.querySelector(`input[value='${pathComponent}']`);
         if (i && !i.checked) {
@@ -159,36 +163,107 @@ const initializeJumps = () => {
     initializeJumpButtons();
 }
 
+// markup of the jump menus comes from the `jump_menu.html` template
+const jumpTemplates = {};
+
+const fillTemplate = (name, values) => {
+    if (!(name in jumpTemplates)) {
+        const template = document.querySelector(`template[data-jump-template="${name}"]`);
+        jumpTemplates[name] = template ? template.innerHTML : '';
+    }
+    return jumpTemplates[name].replace(/\{(\w+)\}/g, (placeholder, key) => key in values ? values[key] : placeholder)
+}
+
 const buildInnerHTMLForJump = (jump_data) => {
-    const def = renderButton(jump_data['def'])
-    const refs = jump_data['refs'].map((ref) => renderButton(ref)).join('\n')
-
-    return `<div class="jump__content jump__content--below">
-        <div class="tab-container">
-            <div class="tab-headers">
-                <div class="tab-header active jump-definitions">Definitions</div>
-                <div class="tab-header jump-references">References</div>
-            </div>
-
-            <div class="jump-definitions tab-content">
-                ${def}
-            </div>
-            <div class="jump-references tab-content hide">
-                ${refs}
-            </div>
-        </div>
-    </div>`
+    const tabs = [
+        ['jump-definitions', 'Definitions', renderDefinitions(jump_data['defs'])],
+        ['jump-references', 'References', renderReferences(jump_data['refs'])],
+    ];
+    if (jump_data['calls']) {
+        tabs.push(['jump-calls', 'Calls', renderCallHierarchy(jump_data['calls'])]);
+    }
+    const headers = tabs
+        .map(([name, title], i) => fillTemplate('tab-header', { name, title, active: i === 0 ? ' active' : '' }))
+        .join('\n');
+    const contents = tabs
+        .map(([name, _, body], i) => fillTemplate('tab-content', { name, body, hidden: i === 0 ? '' : ' hide' }))
+        .join('\n');
+    return fillTemplate('menu', { headers, contents }).trim()
 }
 
 const renderButton = (jumpDest) => {
-    const f = jumpDest['file'];
-    const l = jumpDest['loc']['line'];
-    return `<div class="row jump-button" jump_file='${f}' jump_line='${l}'>${f}:${l}</div>`
+    const file = jumpDest['file'];
+    const line = jumpDest['loc']['line'];
+    const title = jumpDest['title'] || `${file}:${line}`;
+    if (jumpDest['url']) {
+        return fillTemplate('external', { url: jumpDest['url'], title })
+    }
+    return fillTemplate('button', { file, line, title })
+}
+
+const renderDefinitions = (defs) => {
+    if (!defs.length) {
+        return fillTemplate('no-definitions', {})
+    }
+    return defs.map((def) => renderButton(def)).join('\n')
+}
+
+const REFERENCE_GROUPS = [
+    ['write', 'Writes'],
+    ['read', 'Reads'],
+    ['import', 'Imports'],
+    [undefined, 'Other'],
+];
+
+const renderReferences = (refs) => {
+    if (refs.every((ref) => !ref['cat'])) {
+        return refs.map((ref) => renderButton(ref)).join('\n')
+    }
+    return REFERENCE_GROUPS
+        .map(([category, title]) => {
+            const group = refs.filter((ref) => ref['cat'] === category);
+            if (!group.length) {
+                return ''
+            }
+            return fillTemplate('references-group', {
+                category: category || 'other',
+                title,
+                count: group.length,
+                rows: group.map((ref) => renderButton(ref)).join('\n'),
+            })
+        })
+        .join('\n')
+}
+
+const renderCallHierarchy = (calls) => {
+    const renderBranch = (title, items) => {
+        const rows = items.length
+            ? items.map((call) => renderCallButton(call)).join('\n')
+            : fillTemplate('no-calls', {});
+        return fillTemplate('calls-branch', { title, count: items.length, rows })
+    }
+    return renderBranch('Incoming calls', calls['in']) + renderBranch('Outgoing calls', calls['out'])
+}
+
+const renderCallButton = (call) => {
+    return fillTemplate('call', {
+        file: call['file'],
+        line: call['loc']['line'],
+        name: call['name'],
+        count: call['count'] > 1 ? ` &times;${call['count']}` : '',
+    })
 }
 
 const onFileChanged = () => {
     initializeJumps();
     initializeFolds();
+    initializeMacroExpansions();
+    initializeRunCommands();
+    initializeFileViews();
+    initializeFileLinks();
+    initializeSortableTables();
+    initializeOutline();
+    initializeBreadcrumbs();
     initializeHoverTimers();
 }
 
@@ -227,6 +302,182 @@ const initializeFolds = () => {
     });
 }
 
+// alternative views of a file, e.g. rendered markdown and its source
+const initializeFileViews = () => {
+    document.querySelectorAll('.code-section .file-views').forEach(views => {
+        const tabs = views.querySelectorAll('.file-view-tab');
+        tabs.forEach(tab => {
+            tab.onclick = () => {
+                const name = tab.getAttribute('data-view');
+                tabs.forEach(t => t.classList.toggle('active', t === tab))
+                views.querySelectorAll('.file-view').forEach(view => {
+                    view.classList.toggle('hide', view.getAttribute('data-view') !== name)
+                })
+            }
+        })
+    });
+}
+
+// tables of the report pages are sorted by the clicked column
+const initializeSortableTables = () => {
+    document.querySelectorAll('.code-section .sortable-table').forEach(table => {
+        const headers = table.querySelectorAll('th');
+        headers.forEach((header, column) => {
+            header.onclick = () => {
+                const numeric = header.hasAttribute('data-numeric');
+                const descending = header.getAttribute('data-order') !== 'desc';
+                headers.forEach(h => h.removeAttribute('data-order'));
+                header.setAttribute('data-order', descending ? 'desc' : 'asc');
+                const value = (row) => row.cells[column].textContent.trim();
+                const body = table.tBodies[0];
+                Array.from(body.rows)
+                    .sort((a, b) => {
+                        const order = numeric ? value(a) - value(b) : value(a).localeCompare(value(b));
+                        return descending ? -order : order
+                    })
+                    .forEach(row => body.appendChild(row))
+            }
+        })
+    })
+}
+
+// links to other files of the report, e.g. from rendered markdown
+const initializeFileLinks = (root = '.code-section') => {
+    document.querySelectorAll(`${root} .file-link`).forEach(link => {
+        link.onclick = (e) => {
+            e.preventDefault();
+            const file = link.getAttribute('data-file');
+            const line = link.getAttribute('data-line');
+            treeClick(file)
+            jumpTo({
+                file: file,
+                loc: {
+                    line: line,
+                },
+            }, {
+                file: current_file,
+                loc: {
+                    line: null,
+                },
+            }, true)
+        }
+    });
+}
+
+// modes of the left panel: files, modules and outline of the current file
+const initializeTreeModes = () => {
+    const modes = document.querySelectorAll('.tree-mode');
+    modes.forEach(mode => {
+        mode.onclick = () => {
+            const name = mode.getAttribute('data-mode');
+            modes.forEach(m => m.classList.toggle('active', m === mode))
+            document.querySelectorAll('.tree-panel').forEach(panel => {
+                panel.classList.toggle('hide', panel.getAttribute('data-mode') !== name)
+            })
+        }
+    });
+    initializeFileLinks('.tree-panel[data-mode=modules]');
+}
+
+// the theme chosen by the reader is remembered across reports
+const initializeThemeSwitcher = () => {
+    const switcher = document.querySelector('.theme-switcher');
+    const root = document.querySelector('.content');
+    if (!switcher || !root) {
+        return
+    }
+    const setTheme = (theme) => {
+        root.setAttribute('data-theme', theme);
+        switcher.value = theme;
+    }
+    const saved = localStorage.getItem('theme');
+    if (saved && Array.from(switcher.options).some(option => option.value === saved)) {
+        setTheme(saved);
+    }
+    switcher.onchange = () => {
+        setTheme(switcher.value);
+        localStorage.setItem('theme', switcher.value);
+    }
+}
+
+const initializeOutline = () => {
+    const panel = document.querySelector('.tree-panel[data-mode=outline]');
+    const outline = content.querySelector('.file-outline');
+    if (outline && outline.innerHTML.trim()) {
+        panel.innerHTML = outline.innerHTML;
+        initializeFileLinks('.tree-panel[data-mode=outline]');
+    } else {
+        const empty = document.querySelector('template[data-outline-template="empty"]');
+        panel.innerHTML = empty ? empty.innerHTML : '';
+    }
+}
+
+// breadcrumbs with the scope of the first visible line
+var scopes = [];
+var breadcrumbsFrame = null;
+
+const initializeBreadcrumbs = () => {
+    const element = content.querySelector('.file-scopes');
+    scopes = element ? JSON.parse(element.getAttribute('data-scopes')) : [];
+    updateBreadcrumbs();
+}
+
+const updateBreadcrumbs = () => {
+    breadcrumbsFrame = null;
+    const bar = document.querySelector('.breadcrumbs');
+    const rect = content.getBoundingClientRect();
+    const element = document.elementFromPoint(rect.left + rect.width / 2, rect.top + 2);
+    const row = element ? element.closest('.code-section tr.table-line') : null;
+    let scope = row ? row.getAttribute('data-scope') : null;
+    const path = [];
+    while (scope !== null && scopes[scope]) {
+        path.unshift(scopes[scope]['label']);
+        scope = scopes[scope]['parent'];
+    }
+    bar.classList.toggle('hide', scopes.length === 0);
+    bar.textContent = '';
+    path.forEach((label, index) => {
+        if (index > 0) {
+            bar.append(' \u203A ');
+        }
+        const crumb = document.createElement('span');
+        crumb.className = 'breadcrumb';
+        crumb.textContent = label;
+        bar.append(crumb);
+    });
+}
+
+content.addEventListener('scroll', () => {
+    if (breadcrumbsFrame === null) {
+        breadcrumbsFrame = requestAnimationFrame(updateBreadcrumbs);
+    }
+});
+
+// macro expansions
+const initializeMacroExpansions = () => {
+    document.querySelectorAll('.code-section .macro-expand-toggle').forEach(toggle => {
+        const line = toggle.getAttribute('data-line');
+        toggle.onclick = () => {
+            const expansion = document.querySelector(`.code-section tr[data-expansion-line='${line}']`);
+            const expanded = expansion.classList.toggle('hide') === false;
+            toggle.classList.toggle('expanded', expanded);
+            toggle.innerHTML = expanded ? '&minus;' : '+';
+        }
+    });
+}
+
+// commands to run doctests and tests, copied on click
+const initializeRunCommands = () => {
+    document.querySelectorAll('.code-section .run-command').forEach(button => {
+        button.onclick = () => {
+            navigator.clipboard.writeText(button.getAttribute('data-command')).then(() => {
+                button.classList.add('copied');
+                setTimeout(() => button.classList.remove('copied'), 1000);
+            });
+        }
+    });
+}
+
 const closeLines = (lines, fold, mainLine) => {
     fold.classList.remove('arrow--right');
     fold.classList.add('arrow--down');
@@ -296,21 +547,13 @@ const initializeJumpsMenu = () => {
     });
 
     document.querySelectorAll('.code-section .tab-container').forEach((tab) => {
-        const def_header = tab.querySelector('.tab-header.jump-definitions');
-        const ref_header = tab.querySelector('.tab-header.jump-references');
-
-        def_header.addEventListener('click', (e) => {
-            tab.querySelector('.tab-content.jump-references').classList.add('hide')
-            tab.querySelector('.tab-content.jump-definitions').classList.remove('hide')
-            ref_header.classList.remove('active')
-            def_header.classList.add('active')
-        })
-
-        tab.querySelector('.tab-header.jump-references').addEventListener('click', (e) => {
-            tab.querySelector('.tab-content.jump-definitions').classList.add('hide')
-            tab.querySelector('.tab-content.jump-references').classList.remove('hide')
-            def_header.classList.remove('active')
-            ref_header.classList.add('active')
+        const headers = tab.querySelectorAll('.tab-header');
+        headers.forEach((header) => {
+            header.addEventListener('click', (e) => {
+                const name = header.getAttribute('data-tab');
+                headers.forEach(h => h.classList.toggle('active', h === header))
+                tab.querySelectorAll('.tab-content').forEach(c => c.classList.toggle('hide', !c.classList.contains(name)))
+            })
         })
     })
 }
@@ -353,6 +596,9 @@ const initializeHoverTimers = () => {
 
 const main = () => {
     initializeResize();
+    initializeTreeModes();
+    initializeThemeSwitcher();
+    initializeFileLinks('.project-header');
     update();
     onFileChanged();
 }
@@ -1,41 +1,157 @@
 use crate::{
     args::Settings,
-    parser,
-    render::{HtmlGenerator, MyPath, ReportGenerator, SyntaxProcessor},
+    coverage::Coverage,
+    git::{self, Churn},
+    metadata, parser,
+    render::{
+        dead_code_page, metrics_page, override_templates, overview_page, render_markdown,
+        render_module_tree, tasks_page, tests_page, unsafe_page, CrateRoot, ExternalCrates,
+        HtmlGenerator, ModuleNode, MyPath, ProjectInfo, ReportContent, ReportGenerator,
+        SyntaxProcessor,
+    },
+    theme::Themes,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub fn run_report_generator(settings: &Settings) -> Result<(), anyhow::Error> {
     let root = settings.dir.clone();
     if !root.is_dir() {
         return Err(anyhow::anyhow!("dir argument is not actual directory"));
     };
+    let themes = Themes::new(&settings.theme, settings.theme_file.as_deref())?;
+    if let Some(dir) = &settings.templates {
+        override_templates(dir)?;
+    }
     let (host, vfs) = parser::get_analysis(&root, settings.scan_whole)?;
-    let files = parser::scan(&root, &vfs)?;
-    let filenames: Vec<MyPath> = files
-        .values()
-        .map(|file_info| MyPath::new(&file_info.relative_path))
+    let external_crates = ExternalCrates::new(host.raw_database(), &vfs, settings);
+    let roots = external_crates.report_roots(settings);
+    let files = parser::scan(&roots, &vfs)?;
+    // keep the project tree first, included crates follow it
+    let filenames: Vec<MyPath> = roots
+        .iter()
+        .flat_map(|report_root| {
+            let prefix = format!("{}/", report_root.name);
+            files
+                .keys()
+                .filter(move |file_name| file_name.starts_with(&prefix))
+                .map(|file_name| MyPath::new(file_name))
+        })
         .collect();
     let rust_files = files.iter().filter_map(|f| f.1.ra_file_id).collect();
-    let processor = SyntaxProcessor::new(host, vfs, rust_files);
-    let generator = HtmlGenerator::new();
+    let packages = load_packages(settings);
+    let processor = SyntaxProcessor::new(host, vfs, rust_files, external_crates, packages.clone());
+    let coverage = match &settings.coverage {
+        Some(path) => Coverage::load(path, &settings.dir)?,
+        None => Coverage::default(),
+    };
+    let generator = HtmlGenerator::new(&files, coverage);
+    let readme = files
+        .get(&format!("{}/README.md", settings.project_name))
+        .map(|readme| render_markdown(&readme.content, &readme.name, generator.known_files()));
+    let project_prefix = format!("{}/", settings.project_name);
+    let languages = metadata::language_stats(
+        files
+            .iter()
+            .filter(|(file_name, _)| file_name.starts_with(&project_prefix))
+            .map(|(_, file)| (file.path.as_path(), file.content.as_str())),
+    );
     let report_generator = ReportGenerator::default();
 
+    let mut summaries = BTreeMap::new();
     let files_content: HashMap<String, String> = files
         .into_iter()
         .map(|(file_name, file_info)| {
-            generator
-                .generate(&processor, file_info, settings)
-                .map(|content| (file_name, content))
+            let rendered = generator.generate(&processor, file_info, settings)?;
+            summaries.insert(file_name.clone(), rendered.summary);
+            Ok((file_name, rendered.html))
         })
-        .collect::<Result<_, _>>()?;
+        .collect::<Result<_, anyhow::Error>>()?;
+    let module_trees = processor.get_module_trees(settings);
+    let modules = render_module_tree(&module_trees)?;
+    let coverage = summaries
+        .iter()
+        .filter_map(|(file_name, summary)| Some((file_name.clone(), summary.coverage?)))
+        .collect();
+    let churn = if settings.history {
+        load_churn(settings)
+    } else {
+        Churn::default()
+    };
 
-    let output = report_generator.generate(
+    let project = ProjectInfo {
+        name: settings.project_name.clone(),
+        root: settings.dir.to_string_lossy().to_string(),
+        generator_version: env!("CARGO_PKG_VERSION"),
+        rust_analyzer_version: rust_analyzer::version().to_string(),
+        generated_at: metadata::format_time(git::now()),
+        packages,
+        crate_roots: crate_roots(&module_trees),
+        files: languages.iter().map(|stats| stats.files).sum(),
+        lines: languages.iter().map(|stats| stats.lines).sum(),
+        languages,
+    };
+    let overview = overview_page(&project, readme.as_deref())?;
+    let report = ReportContent {
         filenames,
-        files_content,
-        root.file_name().unwrap().to_str().unwrap(),
-        settings.no_compress,
-    );
+        files: files_content,
+        overview: overview.html.clone(),
+        pages: vec![
+            overview,
+            unsafe_page(&summaries)?,
+            tasks_page(&summaries)?,
+            tests_page(&summaries)?,
+            metrics_page(&summaries)?,
+            dead_code_page(&summaries)?,
+        ],
+        readme,
+        modules,
+        coverage,
+        churn,
+        themes,
+        project,
+    };
+    let output = report_generator.generate(report, settings.no_compress)?;
     std::fs::write(&settings.output, output).expect("unable to write file");
     Ok(())
 }
+
+/// Packages of the project, the overview shows only the name of the directory without them
+fn load_packages(settings: &Settings) -> Vec<metadata::Package> {
+    metadata::packages(&settings.dir, &settings.project_name).unwrap_or_else(|error| {
+        println!("WARN: cannot read cargo metadata of the project: {error}");
+        vec![]
+    })
+}
+
+/// Root files of the crates which are rendered in the report
+fn crate_roots(module_trees: &[ModuleNode]) -> Vec<CrateRoot> {
+    module_trees
+        .iter()
+        .filter_map(|root| {
+            let jump = root.jump.as_ref().filter(|jump| jump.url.is_none())?;
+            Some(CrateRoot {
+                name: root.name.clone(),
+                file: jump.file.clone(),
+                line: jump.location.line,
+            })
+        })
+        .collect()
+}
+
+/// Churn of the project with the paths of the tree
+fn load_churn(settings: &Settings) -> Churn {
+    match git::churn(&settings.dir, settings.churn_days) {
+        Ok(churn) => Churn {
+            days: churn.days,
+            commits: churn
+                .commits
+                .into_iter()
+                .map(|(path, commits)| (format!("{}/{path}", settings.project_name), commits))
+                .collect(),
+        },
+        Err(error) => {
+            println!("WARN: cannot read churn of the project: {error}");
+            Churn::default()
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Args, Parser};
 use std::{fs, path::PathBuf};
 
 /// Simple program to greet a person
@@ -14,11 +14,100 @@ pub struct Settings {
     #[clap(short, long, value_parser, default_value = "output.html")]
     pub output: String,
 
+    /// Load the sysroot and render `std`, `core` and `alloc` alongside the project,
+    /// their definitions are linked to the rendered sources instead of doc.rust-lang.org
     #[clap(short, long, value_parser, default_value_t = false)]
     pub scan_whole: bool,
 
     #[clap(short, long, value_parser, default_value_t = false)]
     pub no_compress: bool,
+
+    /// URL of the rendered source for files of dependencies.
+    /// `{crate}`, `{version}`, `{path}` and `{line}` are substituted
+    #[clap(
+        long,
+        value_parser,
+        default_value = "https://docs.rs/crate/{crate}/{version}/source/{path}#{line}"
+    )]
+    pub external_url_template: String,
+
+    /// Render sources of the dependency crate alongside the project
+    #[clap(long = "include-crate", value_parser)]
+    pub include_crates: Vec<String>,
+
+    /// Coverage of `cargo llvm-cov`: an lcov file or a JSON export
+    #[clap(long, value_parser)]
+    pub coverage: Option<PathBuf>,
+
+    /// Show the commit, author and age of the lines from the git repository of the project
+    #[clap(long, value_parser, default_value_t = false)]
+    pub blame: bool,
+
+    /// Add the commits of the files and a heat map of recent commits to the tree
+    #[clap(long, value_parser, default_value_t = false)]
+    pub history: bool,
+
+    /// Period of the commits in the heat map of the tree
+    #[clap(long, value_parser, default_value_t = 90)]
+    pub churn_days: u32,
+
+    /// Theme selected until the reader picks another one: `dark`, `light`, `high-contrast`
+    /// or the name of the theme of `--theme-file`
+    #[clap(long, value_parser, default_value = "dark")]
+    pub theme: String,
+
+    /// JSON theme mapping the palette and the highlight classes to colors,
+    /// or a VS Code color theme or `.tmTheme` named after the file
+    #[clap(long, value_parser)]
+    pub theme_file: Option<PathBuf>,
+
+    /// Mark TODO comments and calls of `todo!`, `unimplemented!`, `unreachable!`
+    /// and `panic!` in the gutter, they are listed on the tasks page anyway
+    #[clap(long, value_parser, default_value_t = false)]
+    pub task_markers: bool,
+
+    /// Directory of Tera templates replacing the built-in ones with the same names
+    #[clap(long, value_parser)]
+    pub templates: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub highlight: HighlightOptions,
+}
+
+/// Optional kinds of semantic highlighting, the same as the options of rust-analyzer
+#[derive(Args, Debug)]
+pub struct HighlightOptions {
+    /// Highlight escape sequences and format arguments of strings, byte strings and C strings
+    #[clap(long = "highlight-strings", value_parser, default_value_t = false)]
+    pub strings: bool,
+
+    /// Highlight punctuation
+    #[clap(long = "highlight-punctuation", value_parser, default_value_t = false)]
+    pub punctuation: bool,
+
+    /// Use specific classes for brackets, commas, colons, etc. instead of `punctuation`
+    #[clap(long = "specialize-punctuation", value_parser, default_value_t = false)]
+    pub specialize_punctuation: bool,
+
+    /// Highlight operators
+    #[clap(long = "highlight-operators", value_parser, default_value_t = false)]
+    pub operators: bool,
+
+    /// Use specific classes for arithmetic, bitwise, comparison and logical operators
+    #[clap(long = "specialize-operators", value_parser, default_value_t = false)]
+    pub specialize_operators: bool,
+
+    /// Highlight `!` of macro calls
+    #[clap(long = "highlight-macro-bang", value_parser, default_value_t = false)]
+    pub macro_bang: bool,
+
+    /// Highlight names which cannot be resolved by their syntax
+    #[clap(
+        long = "highlight-syntactic-name-refs",
+        value_parser,
+        default_value_t = false
+    )]
+    pub syntactic_name_refs: bool,
 }
 
 impl Settings {
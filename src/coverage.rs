@@ -0,0 +1,233 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Hit counts of lines and branches loaded from an lcov or `llvm-cov export` JSON file
+#[derive(Debug, Default, Clone)]
+pub struct Coverage {
+    files: HashMap<PathBuf, FileCoverage>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FileCoverage {
+    /// Hit counts of the instrumented lines, numbered from 1
+    pub lines: BTreeMap<u32, LineCoverage>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct LineCoverage {
+    pub hits: u64,
+    pub branches_covered: u32,
+    pub branches_total: u32,
+}
+
+/// Covered lines out of the instrumented ones
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct CoverageTotals {
+    pub covered: usize,
+    pub total: usize,
+}
+
+impl Coverage {
+    /// Loads the file, relative paths of the sources are resolved from `dir`
+    pub fn load(path: &Path, dir: &Path) -> Result<Self, anyhow::Error> {
+        let content = fs::read_to_string(path)?;
+        let files = if content.trim_start().starts_with('{') {
+            parse_llvm_cov_json(&content)?
+        } else {
+            parse_lcov(&content)
+        };
+        let files = files
+            .into_iter()
+            .map(|(path, coverage)| {
+                let path = dir.join(path);
+                (fs::canonicalize(&path).unwrap_or(path), coverage)
+            })
+            .collect();
+        Ok(Self { files })
+    }
+
+    pub fn file(&self, path: &Path) -> Option<&FileCoverage> {
+        self.files.get(path)
+    }
+}
+
+impl FileCoverage {
+    pub fn totals(&self) -> CoverageTotals {
+        CoverageTotals {
+            covered: self.lines.values().filter(|line| line.hits > 0).count(),
+            total: self.lines.len(),
+        }
+    }
+}
+
+impl CoverageTotals {
+    pub fn add(&mut self, other: CoverageTotals) {
+        self.covered += other.covered;
+        self.total += other.total;
+    }
+
+    pub fn percent(&self) -> Option<usize> {
+        (self.total > 0).then(|| self.covered * 100 / self.total)
+    }
+}
+
+fn parse_lcov(content: &str) -> HashMap<PathBuf, FileCoverage> {
+    let mut files = HashMap::new();
+    let mut current: Option<(PathBuf, FileCoverage)> = None;
+    for line in content.lines() {
+        let (key, value) = line.split_once(':').unwrap_or((line, ""));
+        match (key.trim(), current.as_mut()) {
+            ("SF", _) => current = Some((PathBuf::from(value), FileCoverage::default())),
+            ("DA", Some((_, coverage))) => {
+                let mut fields = value.split(',');
+                let line = fields.next().and_then(|line| line.parse().ok());
+                let hits = fields.next().and_then(|hits| hits.parse::<u64>().ok());
+                if let (Some(line), Some(hits)) = (line, hits) {
+                    coverage.lines.entry(line).or_default().hits += hits;
+                }
+            }
+            ("BRDA", Some((_, coverage))) => {
+                let fields: Vec<_> = value.split(',').collect();
+                if let (Some(line), Some(taken)) = (fields.first(), fields.get(3)) {
+                    if let Ok(line) = line.parse() {
+                        let line = coverage.lines.entry(line).or_default();
+                        line.branches_total += 1;
+                        if taken.parse::<u64>().map_or(false, |taken| taken > 0) {
+                            line.branches_covered += 1;
+                        }
+                    }
+                }
+            }
+            ("end_of_record", _) => {
+                if let Some((path, coverage)) = current.take() {
+                    files.insert(path, coverage);
+                }
+            }
+            _ => (),
+        }
+    }
+    files
+}
+
+/// Parses `cargo llvm-cov --json` (`llvm-cov export`) output.
+/// Line counts follow `llvm-cov`: the maximum of the counts of the regions
+/// which start on the line and of the region which continues from the previous line.
+fn parse_llvm_cov_json(content: &str) -> Result<HashMap<PathBuf, FileCoverage>, anyhow::Error> {
+    let export: Value = serde_json::from_str(content)?;
+    let files = export["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|data| data["files"].as_array().into_iter().flatten());
+    let mut result: HashMap<PathBuf, FileCoverage> = HashMap::new();
+    for file in files {
+        let name = match file["filename"].as_str() {
+            Some(name) => name,
+            None => continue,
+        };
+        let coverage = result.entry(PathBuf::from(name)).or_default();
+        let segments: Vec<_> = file["segments"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Segment::parse)
+            .collect();
+        // count of the region which continues from the previous line
+        let mut region: Option<u64> = None;
+        let mut index = 0;
+        let first_line = segments.first().map_or(1, |segment| segment.line);
+        let last_line = segments.last().map_or(0, |segment| segment.line);
+        for line in first_line..=last_line {
+            let mut hits = region;
+            while index < segments.len() && segments[index].line == line {
+                let segment = &segments[index];
+                if segment.has_count && segment.is_region_entry && !segment.is_gap {
+                    hits = Some(hits.unwrap_or(0).max(segment.count));
+                }
+                region = segment.has_count.then_some(segment.count);
+                index += 1;
+            }
+            if let Some(hits) = hits {
+                let line = coverage.lines.entry(line).or_default();
+                line.hits = line.hits.max(hits);
+            }
+        }
+        for branch in file["branches"].as_array().into_iter().flatten() {
+            let line = branch[0].as_u64();
+            let taken = branch[4].as_u64();
+            let not_taken = branch[5].as_u64();
+            if let (Some(line), Some(taken), Some(not_taken)) = (line, taken, not_taken) {
+                let line = coverage.lines.entry(line as u32).or_default();
+                line.branches_total += 2;
+                line.branches_covered += (taken > 0) as u32 + (not_taken > 0) as u32;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// `[line, column, count, has_count, is_region_entry, is_gap_region]`
+struct Segment {
+    line: u32,
+    count: u64,
+    has_count: bool,
+    is_region_entry: bool,
+    is_gap: bool,
+}
+
+impl Segment {
+    fn parse(value: &Value) -> Option<Self> {
+        Some(Self {
+            line: value[0].as_u64()? as u32,
+            count: value[2].as_u64()?,
+            has_count: value[3].as_bool()?,
+            is_region_entry: value[4].as_bool()?,
+            is_gap: value[5].as_bool().unwrap_or(false),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lcov() {
+        let content =
+            "TN:\nSF:src/lib.rs\nDA:1,3\nDA:2,0\nBRDA:1,0,0,2\nBRDA:1,0,1,-\nend_of_record\n";
+        let files = parse_lcov(content);
+        let coverage = &files[&PathBuf::from("src/lib.rs")];
+        assert_eq!(coverage.lines[&1].hits, 3);
+        assert_eq!(coverage.lines[&1].branches_covered, 1);
+        assert_eq!(coverage.lines[&1].branches_total, 2);
+        assert_eq!(coverage.totals().covered, 1);
+        assert_eq!(coverage.totals().total, 2);
+    }
+
+    #[test]
+    fn test_parse_llvm_cov_json() {
+        let content = r#"{"data": [{"files": [{
+            "filename": "/p/src/lib.rs",
+            "segments": [
+                [1, 1, 5, true, true, false], [2, 5, 0, true, true, false],
+                [2, 9, 5, true, false, false], [3, 2, 0, false, false, false],
+                [5, 1, 0, true, true, false], [6, 2, 0, false, false, false]
+            ],
+            "branches": [[2, 8, 2, 12, 5, 0, 0, 0, 4]]
+        }]}]}"#;
+        let files = parse_llvm_cov_json(content).unwrap();
+        let coverage = &files[&PathBuf::from("/p/src/lib.rs")];
+        let hits: Vec<_> = coverage
+            .lines
+            .iter()
+            .map(|(line, c)| (*line, c.hits))
+            .collect();
+        assert_eq!(hits, vec![(1, 5), (2, 5), (3, 5), (5, 0), (6, 0)]);
+        assert_eq!(coverage.lines[&2].branches_covered, 1);
+    }
+}
@@ -0,0 +1,219 @@
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Commit which last changed a line, from `git blame`
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct BlameLine {
+    pub commit: String,
+    pub author: String,
+    /// Unix time of the commit
+    pub time: i64,
+    pub summary: String,
+}
+
+impl BlameLine {
+    pub fn is_committed(&self) -> bool {
+        self.commit.chars().any(|c| c != '0')
+    }
+}
+
+/// Commit of the history of a file, from `git log`
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    /// Unix time of the commit
+    pub time: i64,
+    pub summary: String,
+}
+
+/// Numbers of recent commits which changed the files and directories of the project,
+/// by their paths relative to it. Directories end with `/`, the project itself is `""`
+#[derive(Debug, Default, Clone)]
+pub struct Churn {
+    pub days: u32,
+    pub commits: HashMap<String, usize>,
+}
+
+/// Runs `git blame` in the local repository of `dir`
+pub fn blame(dir: &Path, file: &Path) -> Result<Vec<BlameLine>, anyhow::Error> {
+    let output = git(dir, &["blame", "--line-porcelain", "--"], file)?;
+    Ok(parse_line_porcelain(&output))
+}
+
+/// Commits which changed the file, the newest first
+pub fn history(dir: &Path, file: &Path) -> Result<Vec<Commit>, anyhow::Error> {
+    let output = git(
+        dir,
+        &["log", "--follow", "--format=%H%x1f%an%x1f%at%x1f%s", "--"],
+        file,
+    )?;
+    Ok(output.lines().filter_map(parse_commit).collect())
+}
+
+/// Counts commits of the last `days` which changed the files of `dir`
+pub fn churn(dir: &Path, days: u32) -> Result<Churn, anyhow::Error> {
+    let since = format!("--since={days} days ago");
+    let output = git(
+        dir,
+        &["log", &since, "--relative", "--name-only", "--format=%x1e"],
+        Path::new("."),
+    )?;
+    Ok(Churn {
+        days,
+        commits: count_changes(&output),
+    })
+}
+
+fn parse_commit(line: &str) -> Option<Commit> {
+    let mut fields = line.split('\x1f');
+    Some(Commit {
+        hash: fields.next()?.to_string(),
+        author: fields.next()?.to_string(),
+        time: fields.next()?.parse().ok()?,
+        summary: fields.next().unwrap_or_default().to_string(),
+    })
+}
+
+/// Counts every commit once for each file and directory it changed
+fn count_changes(output: &str) -> HashMap<String, usize> {
+    let mut commits = HashMap::new();
+    for commit in output.split('\x1e') {
+        let mut paths = HashSet::new();
+        for file in commit.lines().filter(|line| !line.is_empty()) {
+            paths.insert(file.to_string());
+            paths.insert(String::new());
+            for (index, _) in file.match_indices('/') {
+                paths.insert(file[..=index].to_string());
+            }
+        }
+        for path in paths {
+            *commits.entry(path).or_default() += 1;
+        }
+    }
+    commits
+}
+
+fn git(dir: &Path, args: &[&str], file: &Path) -> Result<String, anyhow::Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .arg(file)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn parse_line_porcelain(output: &str) -> Vec<BlameLine> {
+    let mut lines = vec![];
+    let mut current = BlameLine::default();
+    for line in output.lines() {
+        // content of the line ends its annotation
+        if line.starts_with('\t') {
+            lines.push(std::mem::take(&mut current));
+            continue;
+        }
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "author" => current.author = value.to_string(),
+            "author-time" => current.time = value.parse().unwrap_or_default(),
+            "summary" => current.summary = value.to_string(),
+            _ if current.commit.is_empty() && is_commit_hash(key) => {
+                current.commit = key.to_string()
+            }
+            _ => (),
+        }
+    }
+    lines
+}
+
+fn is_commit_hash(text: &str) -> bool {
+    matches!(text.len(), 40 | 64) && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
+/// Approximate age like `3 days ago`
+pub fn format_age(seconds: i64) -> String {
+    let units = [
+        (365 * 24 * 3600, "year"),
+        (30 * 24 * 3600, "month"),
+        (7 * 24 * 3600, "week"),
+        (24 * 3600, "day"),
+        (3600, "hour"),
+        (60, "minute"),
+    ];
+    units.iter().find(|(unit, _)| seconds >= *unit).map_or(
+        "just now".to_string(),
+        |(unit, name)| {
+            let count = seconds / unit;
+            let plural = if count == 1 { "" } else { "s" };
+            format!("{count} {name}{plural} ago")
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_porcelain() {
+        let hash = "a".repeat(40);
+        let output = format!(
+            "{hash} 1 1 2\nauthor Jane\nauthor-time 1600000000\nsummary Add parser\nfilename src/lib.rs\n\tfn main() {{\n\
+             {hash} 2 2\nauthor Jane\nauthor-time 1600000000\nsummary Add parser\nfilename src/lib.rs\n\t}}\n"
+        );
+        let lines = parse_line_porcelain(&output);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].commit, hash);
+        assert_eq!(lines[1].author, "Jane");
+        assert_eq!(lines[1].time, 1600000000);
+        assert_eq!(lines[1].summary, "Add parser");
+    }
+
+    #[test]
+    fn test_count_changes() {
+        let output = "\x1e\n\nsrc/lib.rs\nsrc/git/mod.rs\n\x1e\n\nsrc/lib.rs\nREADME.md\n";
+        let commits = count_changes(output);
+        assert_eq!(commits[""], 2);
+        assert_eq!(commits["src/"], 2);
+        assert_eq!(commits["src/lib.rs"], 2);
+        assert_eq!(commits["src/git/"], 1);
+        assert_eq!(commits["README.md"], 1);
+        assert_eq!(commits.len(), 6);
+    }
+
+    #[test]
+    fn test_parse_commit() {
+        let commit = parse_commit("abc\x1fJane\x1f1600000000\x1fFix: a\x1fb").unwrap();
+        assert_eq!(commit.hash, "abc");
+        assert_eq!(commit.time, 1600000000);
+        assert_eq!(commit.summary, "Fix: a");
+        assert!(parse_commit("").is_none());
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(30), "just now");
+        assert_eq!(format_age(3600), "1 hour ago");
+        assert_eq!(format_age(3 * 24 * 3600 + 5), "3 days ago");
+        assert_eq!(format_age(400 * 24 * 3600), "1 year ago");
+    }
+}
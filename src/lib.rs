@@ -1,7 +1,11 @@
 mod app;
 mod args;
+mod coverage;
+mod git;
+mod metadata;
 mod parser;
 mod render;
+mod theme;
 
 pub use app::run_report_generator;
-pub use args::Settings;
+pub use args::{HighlightOptions, Settings};
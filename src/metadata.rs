@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Languages of the files by their extensions, the rest are counted as `Other`
+const LANGUAGES: &[(&str, &[&str])] = &[
+    ("Rust", &["rs"]),
+    ("TOML", &["toml"]),
+    ("Markdown", &["md", "markdown"]),
+    ("JSON", &["json"]),
+    ("YAML", &["yml", "yaml"]),
+    ("Shell", &["sh", "bash", "zsh"]),
+    ("HTML", &["html", "htm"]),
+    ("CSS", &["css"]),
+    ("JavaScript", &["js", "mjs"]),
+    ("TypeScript", &["ts"]),
+    ("Python", &["py"]),
+    ("C", &["c", "h"]),
+    ("C++", &["cpp", "cc", "hpp"]),
+];
+
+/// Package of the workspace as `cargo metadata` describes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub license: Option<String>,
+    pub repository: Option<String>,
+    #[serde(skip_serializing)]
+    pub manifest_path: PathBuf,
+    /// Name of the `Cargo.toml` in the report
+    #[serde(skip_deserializing)]
+    pub manifest: String,
+    #[serde(default, skip_serializing)]
+    pub targets: Vec<Target>,
+}
+
+/// Target of a package: library, binary, test, etc.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Target {
+    pub name: String,
+    /// `lib`, `proc-macro`, `bin`, `test`, `bench`, `example`, `custom-build`, etc.
+    pub kind: Vec<String>,
+    pub src_path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct LanguageStats {
+    pub language: &'static str,
+    pub files: usize,
+    pub lines: usize,
+}
+
+/// Packages of the workspace in `dir`, the package of its own manifest goes first.
+/// `root_name` is the name of the directory in the report
+pub fn packages(dir: &Path, root_name: &str) -> Result<Vec<Package>, anyhow::Error> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(dir)
+        .output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    parse_packages(&String::from_utf8_lossy(&output.stdout), dir, root_name)
+}
+
+fn parse_packages(
+    metadata: &str,
+    dir: &Path,
+    root_name: &str,
+) -> Result<Vec<Package>, anyhow::Error> {
+    let metadata: Metadata = serde_json::from_str(metadata)?;
+    let mut packages = metadata.packages;
+    for package in packages.iter_mut() {
+        package.manifest = match package.manifest_path.strip_prefix(dir) {
+            Ok(path) => format!("{root_name}/{}", path.to_string_lossy()),
+            Err(_) => String::new(),
+        };
+    }
+    packages.sort_by_key(|package| {
+        (
+            package.manifest_path != dir.join("Cargo.toml"),
+            package.name.clone(),
+        )
+    });
+    Ok(packages)
+}
+
+/// Counts files and lines by languages, the biggest languages go first
+pub fn language_stats<'a>(files: impl Iterator<Item = (&'a Path, &'a str)>) -> Vec<LanguageStats> {
+    let mut counts: HashMap<&'static str, (usize, usize)> = HashMap::new();
+    for (path, content) in files {
+        let count = counts.entry(language(path)).or_default();
+        count.0 += 1;
+        count.1 += content.lines().count();
+    }
+    let mut stats: Vec<_> = counts
+        .into_iter()
+        .map(|(language, (files, lines))| LanguageStats {
+            language,
+            files,
+            lines,
+        })
+        .collect();
+    stats.sort_by_key(|stats| (std::cmp::Reverse(stats.lines), stats.language));
+    stats
+}
+
+fn language(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    LANGUAGES
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&extension))
+        .map_or("Other", |(language, _)| language)
+}
+
+/// UTC time like `2023-02-14 09:30 UTC`
+pub fn format_time(seconds: i64) -> String {
+    let days = seconds.div_euclid(24 * 3600);
+    let time = seconds.rem_euclid(24 * 3600);
+    // days to the civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        time / 3600,
+        time % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_packages() {
+        let metadata = r#"{"packages": [
+            {"name": "member", "version": "0.2.0", "description": null, "authors": [], "license": null,
+             "repository": null, "manifest_path": "/work/app/member/Cargo.toml",
+             "targets": [{"name": "member", "kind": ["lib"], "src_path": "/work/app/member/src/lib.rs"}]},
+            {"name": "app", "version": "1.0.0", "description": "An app", "authors": ["Ann <ann@example.com>"],
+             "license": "MIT", "repository": "https://example.com/app", "manifest_path": "/work/app/Cargo.toml"}
+        ], "workspace_members": []}"#;
+        let packages = parse_packages(metadata, Path::new("/work/app"), "app").unwrap();
+        assert_eq!(packages[0].name, "app");
+        assert_eq!(packages[0].manifest, "app/Cargo.toml");
+        assert_eq!(packages[0].authors, vec!["Ann <ann@example.com>"]);
+        assert_eq!(packages[1].manifest, "app/member/Cargo.toml");
+        assert_eq!(packages[1].license, None);
+        assert_eq!(packages[1].targets[0].kind, vec!["lib"]);
+        assert!(packages[0].targets.is_empty());
+    }
+
+    #[test]
+    fn test_language_stats() {
+        let files = [
+            (Path::new("a/src/lib.rs"), "fn a() {}\nfn b() {}\n"),
+            (Path::new("a/src/main.rs"), "fn main() {}"),
+            (
+                Path::new("a/Cargo.toml"),
+                "[package]\nname = \"a\"\nversion = \"0.1.0\"\n",
+            ),
+            (Path::new("a/LICENSE"), "MIT"),
+        ];
+        let stats = language_stats(files.into_iter());
+        let stats: Vec<_> = stats
+            .iter()
+            .map(|stats| (stats.language, stats.files, stats.lines))
+            .collect();
+        assert_eq!(stats, vec![("Rust", 2, 3), ("TOML", 1, 3), ("Other", 1, 1)]);
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0), "1970-01-01 00:00 UTC");
+        assert_eq!(format_time(951_827_696), "2000-02-29 12:34 UTC");
+        assert_eq!(format_time(1_676_367_000), "2023-02-14 09:30 UTC");
+    }
+}
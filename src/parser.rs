@@ -52,23 +52,28 @@ pub fn get_analysis(
 
 #[derive(Debug)]
 pub struct FileInfo {
+    /// Name of the file in the report: root name followed by the relative path
+    pub name: String,
     pub content: String,
     pub ra_file_id: Option<FileId>,
     pub path: PathBuf,
     pub relative_path: String,
 }
 
-pub fn scan(root: &PathBuf, vfs: &Vfs) -> Result<BTreeMap<String, FileInfo>, anyhow::Error> {
-    let project_name = root
-        .file_name()
-        .ok_or_else(|| anyhow::anyhow!("not a dir"))?
-        .to_string_lossy()
-        .to_string();
+/// Directory shown as a separate top-level tree of the report.
+#[derive(Debug, Clone)]
+pub struct ReportRoot {
+    pub name: String,
+    pub path: PathBuf,
+    /// Whether the directory belongs to a dependency or the sysroot
+    pub external: bool,
+}
+
+pub fn scan(roots: &[ReportRoot], vfs: &Vfs) -> Result<BTreeMap<String, FileInfo>, anyhow::Error> {
     let ignore: Vec<&Path> = vec![
         ".DS_Store",
         ".git",
         "target",
-        "README.md",
         "output_rust_ast.html",
         "output.html",
         "tree_script.js",
@@ -80,44 +85,56 @@ pub fn scan(root: &PathBuf, vfs: &Vfs) -> Result<BTreeMap<String, FileInfo>, any
     .map(Path::new)
     .collect();
     let mut files = BTreeMap::new();
-    for entry in walkdir::WalkDir::new(root)
-        .follow_links(false)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|f| f.path().is_file())
-        .filter(|f| {
-            !f.path()
-                .ancestors()
-                .any(|f| ignore.iter().any(|end| f.ends_with(end)))
-        })
-    {
-        let path = entry.path();
-        println!("INFO: walk to {path:?}");
-        let content = std::fs::read_to_string(path)?;
-        let file_relative_path = path
-            .strip_prefix(root.clone())
-            .expect("failed to extract relative path");
-        let fname = format!("{}/{}", project_name, file_relative_path.to_string_lossy());
-        let is_rust_file: bool = path.extension().map(|e| e == "rs").unwrap_or(false);
-        let ra_file_id = if is_rust_file {
-            let vfs_path = VfsPath::new_real_path(path.to_string_lossy().to_string());
-            let file_id = vfs
-                .file_id(&vfs_path)
-                .ok_or_else(|| anyhow::anyhow!("RA doesnt have rust file"))?;
-            Some(file_id)
-        } else {
-            None
-        };
+    for root in roots {
+        for entry in walkdir::WalkDir::new(&root.path)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|f| f.path().is_file())
+            .filter(|f| {
+                !f.path()
+                    .ancestors()
+                    .any(|f| ignore.iter().any(|end| f.ends_with(end)))
+            })
+        {
+            let path = entry.path();
+            println!("INFO: walk to {path:?}");
+            let content = match std::fs::read_to_string(path) {
+                Ok(content) => content,
+                // dependencies may ship binary files, they are not shown
+                Err(e) if root.external => {
+                    println!("WARN: skip {path:?}: {e}");
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let file_relative_path = path
+                .strip_prefix(&root.path)
+                .expect("failed to extract relative path");
+            let fname = format!("{}/{}", root.name, file_relative_path.to_string_lossy());
+            let is_rust_file: bool = path.extension().map(|e| e == "rs").unwrap_or(false);
+            let ra_file_id = if is_rust_file {
+                let vfs_path = VfsPath::new_real_path(path.to_string_lossy().to_string());
+                match vfs.file_id(&vfs_path) {
+                    Some(file_id) => Some(file_id),
+                    None if root.external => None,
+                    None => return Err(anyhow::anyhow!("RA doesnt have rust file")),
+                }
+            } else {
+                None
+            };
 
-        files.insert(
-            fname,
-            FileInfo {
-                content,
-                ra_file_id,
-                path: path.to_path_buf(),
-                relative_path: file_relative_path.to_string_lossy().to_string(),
-            },
-        );
+            files.insert(
+                fname,
+                FileInfo {
+                    name: fname.clone(),
+                    content,
+                    ra_file_id,
+                    path: path.to_path_buf(),
+                    relative_path: file_relative_path.to_string_lossy().to_string(),
+                },
+            );
+        }
     }
 
     Ok(files)
@@ -1,74 +1,297 @@
 use crate::{
     args::Settings,
+    coverage::{Coverage, CoverageTotals, FileCoverage, LineCoverage},
+    git::{self, BlameLine, Commit},
     parser::FileInfo,
-    render::{static_files, syntax_processor::FoldingRange, SyntaxProcessor},
+    render::{
+        highlighters::{highlight_tokens, highlighter_for},
+        static_files,
+        syntax_processor::{
+            run_commands, FoldingRange, FoldingRanges, FunctionMetrics, RunCommand, RunCommands,
+            Runnable, Scopes,
+        },
+        HtmlToken, MacroExpansion, SyntaxProcessor, TaskKind, UnsafeKind, UnusedItem,
+    },
 };
 use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+};
 use tera::Context;
 use vfs::FileId;
 
+use super::{
+    manifest::{is_manifest, link_manifest, ManifestIndex},
+    markdown::render_markdown,
+    outline::render_outline,
+};
+
 #[derive(Serialize, Clone)]
 struct Line {
     number: usize,
     html_content: String,
     fold: Option<FoldingRange>,
+    expansions: Vec<MacroExpansion>,
+    commands: Vec<RunCommand>,
+    unsafe_kinds: Vec<UnsafeKind>,
+    tasks: Vec<TaskKind>,
+    scope: Option<usize>,
+    coverage: Option<LineCoverage>,
+    blame: Option<LineBlame>,
+    /// Function starting at the line which is too big or complex
+    hotspot: Option<FunctionMetrics>,
+    /// The line belongs to an item which is never used
+    unused: bool,
+}
+
+/// Commit of a line in the blame gutter, the details are shown on the first line of a group
+#[derive(Serialize, Clone)]
+struct LineBlame {
+    commit: String,
+    author: String,
+    age: String,
+    summary: String,
+    first: bool,
+    /// Alternates between neighbouring groups to tell them apart
+    odd: bool,
+}
+
+/// Commit of the history view of a file
+#[derive(Serialize)]
+struct HistoryEntry {
+    hash: String,
+    commit: String,
+    author: String,
+    age: String,
+    summary: String,
+}
+
+/// Data attached to the lines of a rust file by the processor
+#[derive(Default)]
+struct LineAnnotations {
+    folding_ranges: FoldingRanges,
+    commands: RunCommands,
+    scopes: Scopes,
+    coverage: FileCoverage,
+    blame: Vec<LineBlame>,
+    hotspots: HashMap<u32, FunctionMetrics>,
+    unused_items: Vec<UnusedItem>,
+}
+
+/// Html of a file with the facts about it collected for the report pages
+pub struct RenderedFile {
+    pub html: String,
+    pub summary: FileSummary,
+}
+
+impl RenderedFile {
+    fn from_html(html: String) -> Self {
+        Self {
+            html,
+            summary: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct FileSummary {
+    pub unsafe_sites: Vec<UnsafeSite>,
+    pub runnables: Vec<Runnable>,
+    pub coverage: Option<CoverageTotals>,
+    pub metrics: Vec<FunctionMetrics>,
+    pub tasks: Vec<TaskSite>,
+    pub unused_items: Vec<UnusedItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnsafeSite {
+    pub line: usize,
+    pub kinds: Vec<UnsafeKind>,
+    pub code: String,
+}
+
+/// Line with unfinished code, listed on the tasks page
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskSite {
+    pub line: usize,
+    pub kinds: Vec<TaskKind>,
+    pub code: String,
+}
+
+/// One of the alternative representations of a file (e.g. rendered markdown and its source)
+#[derive(Serialize)]
+struct View {
+    name: &'static str,
+    title: &'static str,
+    html: String,
 }
 
 #[derive(Debug, Default, Clone)]
-pub struct HtmlGenerator {}
+pub struct HtmlGenerator {
+    /// Names of all files of the report, used to resolve links between files
+    known_files: HashSet<String>,
+    manifest_index: ManifestIndex,
+    coverage: Coverage,
+}
 
 impl HtmlGenerator {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(files: &BTreeMap<String, FileInfo>, coverage: Coverage) -> Self {
+        Self {
+            known_files: files.keys().cloned().collect(),
+            manifest_index: ManifestIndex::new(files),
+            coverage,
+        }
+    }
+
+    pub fn known_files(&self) -> &HashSet<String> {
+        &self.known_files
     }
     pub fn generate(
         &self,
         processor: &SyntaxProcessor,
         file_info: FileInfo,
         settings: &Settings,
-    ) -> Result<String, anyhow::Error> {
+    ) -> Result<RenderedFile, anyhow::Error> {
         println!("start generate for file '{:?}'", file_info.relative_path);
-        match file_info.ra_file_id {
+        let mut rendered = match file_info.ra_file_id {
             Some(file_id) => {
-                self.generate_rust_file_html(processor, file_id, &file_info.content, settings)
+                self.generate_rust_file_html(processor, file_id, &file_info, settings)?
             }
-            None => self.generate_other_file_html(&file_info.content),
+            None => RenderedFile::from_html(
+                self.generate_other_file_html(processor, &file_info, settings)?,
+            ),
+        };
+        let markdown = file_info.ra_file_id.is_none() && is_markdown(&file_info.path);
+        let history = self.history(&file_info, settings);
+        if !markdown && history.is_empty() {
+            return Ok(rendered);
+        }
+        let mut views = vec![];
+        if markdown {
+            let preview = render_markdown(&file_info.content, &file_info.name, &self.known_files);
+            views.push(View {
+                name: "preview",
+                title: "Preview",
+                html: render_markdown_view(&preview)?,
+            });
         }
+        views.push(View {
+            name: "source",
+            title: if markdown { "Source" } else { "Code" },
+            html: rendered.html,
+        });
+        if !history.is_empty() {
+            views.push(View {
+                name: "history",
+                title: "History",
+                html: render_history(&history)?,
+            });
+        }
+        rendered.html = render_views(&views)?;
+        Ok(rendered)
     }
 
     fn generate_rust_file_html(
         &self,
         processor: &SyntaxProcessor,
         file_id: FileId,
-        file_content: &str,
+        file_info: &FileInfo,
         settings: &Settings,
-    ) -> Result<String, anyhow::Error> {
+    ) -> Result<RenderedFile, anyhow::Error> {
+        let file_content = &file_info.content;
+        let coverage = self.coverage.file(&file_info.path);
         let tokens = processor.process_file(file_id, settings);
-        let folding_ranges = processor.get_folding_ranges(file_id);
-        let lines: Vec<Line> = tokens
-            .split_inclusive(|t| t.is_new_line)
-            .map(|tokens| {
-                tokens
-                    .iter()
-                    .map(|token| token.render(file_content))
-                    .collect::<String>()
+        let runnables = processor.get_runnables(file_id);
+        let metrics = processor.get_metrics(file_id);
+        let annotations = LineAnnotations {
+            folding_ranges: processor.get_folding_ranges(file_id),
+            commands: run_commands(&runnables),
+            scopes: processor.get_scopes(file_id),
+            coverage: coverage.cloned().unwrap_or_default(),
+            blame: self.blame(file_info, settings),
+            hotspots: metrics
+                .iter()
+                .filter(|function| function.hotspot)
+                .map(|function| (function.line, function.clone()))
+                .collect(),
+            unused_items: tokens
+                .iter()
+                .filter_map(|token| token.unused_item.clone())
+                .collect(),
+        };
+        let mut lines = lines_from_tokens(&tokens, file_content, &annotations);
+        let source_lines: Vec<&str> = file_content.lines().collect();
+        let code = |line: &Line| {
+            source_lines
+                .get(line.number - 1)
+                .map_or(String::new(), |code| code.trim().to_string())
+        };
+        let unsafe_sites = lines
+            .iter()
+            .filter(|line| !line.unsafe_kinds.is_empty())
+            .map(|line| UnsafeSite {
+                line: line.number,
+                kinds: line.unsafe_kinds.clone(),
+                code: code(line),
             })
-            .enumerate()
-            .map(|(number, html_content)| {
-                let number = number + 1;
-                Line {
-                    number,
-                    html_content,
-                    //fold: folds.entry(number as u32).or_default().to_vec(),
-                    fold: folding_ranges.get(&(number as u32)).cloned(),
-                }
+            .collect();
+        let tasks = lines
+            .iter()
+            .filter(|line| !line.tasks.is_empty())
+            .map(|line| TaskSite {
+                line: line.number,
+                kinds: line.tasks.clone(),
+                code: code(line),
             })
             .collect();
-        render_lines(&lines)
+        // tasks are listed on their page, the gutter shows them on demand
+        if !settings.task_markers {
+            for line in lines.iter_mut() {
+                line.tasks.clear();
+            }
+        }
+        // moved to the outline panel when the file is opened
+        let outline = render_outline(&file_info.name, &processor.get_outline(file_id))?;
+        Ok(RenderedFile {
+            html: render_rust_file(&outline, &annotations.scopes, &render_lines(&lines)?)?,
+            summary: FileSummary {
+                unsafe_sites,
+                runnables,
+                coverage: coverage.map(FileCoverage::totals),
+                metrics,
+                tasks,
+                unused_items: annotations.unused_items,
+            },
+        })
     }
 
-    fn generate_other_file_html(&self, content: &str) -> Result<String, anyhow::Error> {
-        let content = html_escape::encode_text(&content).to_string();
+    fn generate_other_file_html(
+        &self,
+        processor: &SyntaxProcessor,
+        file_info: &FileInfo,
+        settings: &Settings,
+    ) -> Result<String, anyhow::Error> {
+        let annotations = LineAnnotations {
+            blame: self.blame(file_info, settings),
+            ..Default::default()
+        };
+        if let Some(highlighter) = highlighter_for(&file_info.path) {
+            let mut tokens = highlight_tokens(&file_info.content, highlighter);
+            if is_manifest(&file_info.name) {
+                link_manifest(
+                    &mut tokens,
+                    &file_info.name,
+                    &file_info.content,
+                    &self.manifest_index,
+                    &self.known_files,
+                    processor.external_crates(),
+                );
+            }
+            let lines = lines_from_tokens(&tokens, &file_info.content, &annotations);
+            return render_lines(&lines);
+        }
+        let content = html_escape::encode_text(&file_info.content).to_string();
         let lines = content
             .split('\n')
             .enumerate()
@@ -76,15 +299,186 @@ impl HtmlGenerator {
                 number: number + 1,
                 html_content: html_content.to_string(),
                 fold: Default::default(),
+                expansions: Default::default(),
+                commands: Default::default(),
+                unsafe_kinds: Default::default(),
+                tasks: Default::default(),
+                scope: None,
+                coverage: None,
+                blame: annotations.blame.get(number).cloned(),
+                hotspot: None,
+                unused: false,
             })
             .collect::<Vec<_>>();
         render_lines(&lines)
     }
+
+    /// Blame of the files of the project, dependencies are outside of its repository
+    fn blame(&self, file_info: &FileInfo, settings: &Settings) -> Vec<LineBlame> {
+        if !settings.blame || !file_info.path.starts_with(&settings.dir) {
+            return vec![];
+        }
+        match git::blame(&settings.dir, &file_info.path) {
+            Ok(blame) => blame_lines(&blame, git::now()),
+            Err(error) => {
+                println!("WARN: cannot blame {:?}: {error}", file_info.relative_path);
+                vec![]
+            }
+        }
+    }
+
+    /// Commits of the files of the project
+    fn history(&self, file_info: &FileInfo, settings: &Settings) -> Vec<Commit> {
+        if !settings.history || !file_info.path.starts_with(&settings.dir) {
+            return vec![];
+        }
+        git::history(&settings.dir, &file_info.path).unwrap_or_else(|error| {
+            println!(
+                "WARN: cannot read history of {:?}: {error}",
+                file_info.relative_path
+            );
+            vec![]
+        })
+    }
+}
+
+fn blame_lines(blame: &[BlameLine], now: i64) -> Vec<LineBlame> {
+    let mut odd = true;
+    blame
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            let first = index == 0 || blame[index - 1].commit != line.commit;
+            if first {
+                odd = !odd;
+            }
+            let committed = line.is_committed();
+            LineBlame {
+                commit: if committed {
+                    line.commit.chars().take(7).collect()
+                } else {
+                    "uncommitted".to_string()
+                },
+                author: line.author.clone(),
+                age: if committed {
+                    git::format_age(now - line.time)
+                } else {
+                    String::new()
+                },
+                summary: line.summary.clone(),
+                first,
+                odd,
+            }
+        })
+        .collect()
+}
+
+fn lines_from_tokens(
+    tokens: &[HtmlToken],
+    file_content: &str,
+    annotations: &LineAnnotations,
+) -> Vec<Line> {
+    tokens
+        .split_inclusive(|t| t.is_new_line)
+        .enumerate()
+        .map(|(number, tokens)| {
+            let number = number + 1;
+            let html_content = tokens
+                .iter()
+                .map(|token| token.render(file_content))
+                .collect::<String>();
+            let expansions = tokens
+                .iter()
+                .filter_map(|token| token.macro_expansion.clone())
+                .collect();
+            let mut unsafe_kinds: Vec<_> = tokens
+                .iter()
+                .filter_map(|token| token.unsafe_kind)
+                .collect();
+            unsafe_kinds.sort();
+            unsafe_kinds.dedup();
+            let mut tasks: Vec<_> = tokens.iter().filter_map(|token| token.task_kind).collect();
+            tasks.sort();
+            tasks.dedup();
+            Line {
+                number,
+                html_content,
+                //fold: folds.entry(number as u32).or_default().to_vec(),
+                fold: annotations.folding_ranges.get(&(number as u32)).cloned(),
+                expansions,
+                commands: annotations
+                    .commands
+                    .get(&(number as u32))
+                    .cloned()
+                    .unwrap_or_default(),
+                unsafe_kinds,
+                tasks,
+                scope: annotations.scopes.line_scope(number),
+                coverage: annotations.coverage.lines.get(&(number as u32)).cloned(),
+                blame: annotations.blame.get(number - 1).cloned(),
+                hotspot: annotations.hotspots.get(&(number as u32)).cloned(),
+                unused: annotations
+                    .unused_items
+                    .iter()
+                    .any(|item| (item.start_line..=item.end_line).contains(&(number as u32))),
+            }
+        })
+        .collect()
+}
+
+/// Code of the rust file with its outline and the scopes of the lines for the breadcrumbs,
+/// both are read by the script when the file is opened
+fn render_rust_file(outline: &str, scopes: &Scopes, code: &str) -> Result<String, anyhow::Error> {
+    let mut context = Context::new();
+    context.insert("outline", outline);
+    context.insert("scopes", &serde_json::to_string(&scopes.scopes)?);
+    context.insert("code", code);
+    let result = static_files::templates::render("rust_file.html", &context)?;
+    Ok(result)
+}
+
+fn render_markdown_view(html: &str) -> Result<String, anyhow::Error> {
+    let mut context = Context::new();
+    context.insert("html", html);
+    let result = static_files::templates::render("markdown.html", &context)?;
+    Ok(result)
+}
+
+fn render_history(commits: &[Commit]) -> Result<String, anyhow::Error> {
+    let now = git::now();
+    let commits: Vec<HistoryEntry> = commits
+        .iter()
+        .map(|commit| HistoryEntry {
+            hash: commit.hash.clone(),
+            commit: commit.hash.chars().take(7).collect(),
+            author: commit.author.clone(),
+            age: git::format_age(now - commit.time),
+            summary: commit.summary.clone(),
+        })
+        .collect();
+    let mut context = Context::new();
+    context.insert("commits", &commits);
+    let result = static_files::templates::render("history.html", &context)?;
+    Ok(result)
+}
+
+fn render_views(views: &[View]) -> Result<String, anyhow::Error> {
+    let mut context = Context::new();
+    context.insert("views", &views);
+    let result = static_files::templates::render("views.html", &context)?;
+    Ok(result)
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension().map_or(false, |extension| {
+        extension == "md" || extension == "markdown"
+    })
 }
 
 fn render_lines(lines: &[Line]) -> Result<String, anyhow::Error> {
     let mut context = Context::new();
     context.insert("lines", &lines);
-    let result = static_files::templates::TEMPLATES.render("code.html", &context)?;
+    context.insert("blame", &lines.iter().any(|line| line.blame.is_some()));
+    let result = static_files::templates::render("code.html", &context)?;
     Ok(result)
 }
@@ -1,4 +1,4 @@
-use ide::{HoverResult, LineIndex};
+use ide::{HoverResult, LineIndex, ReferenceCategory};
 use serde::{self, Serialize};
 use serde_with::serde_as;
 use std::{fmt::Display, sync::Arc};
@@ -12,6 +12,71 @@ pub struct HtmlToken {
     pub hover_info: Option<HoverResult>,
     pub type_info: Option<String>,
     pub navigation: Option<Navigation>,
+    pub macro_expansion: Option<MacroExpansion>,
+    pub unsafe_kind: Option<UnsafeKind>,
+    pub task_kind: Option<TaskKind>,
+    /// Set on the name of an item which is never used
+    pub unused_item: Option<UnusedItem>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MacroExpansion {
+    pub name: String,
+    pub html: String,
+}
+
+/// Unsafe code marked in the gutter and listed on the unsafe audit page
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum UnsafeKind {
+    Block,
+    Fn,
+    Impl,
+    Trait,
+    Operation,
+}
+
+/// Unfinished code: a marked comment or a call of a panicking macro
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TaskKind {
+    #[serde(rename = "TODO")]
+    Todo,
+    #[serde(rename = "FIXME")]
+    Fixme,
+    #[serde(rename = "HACK")]
+    Hack,
+    #[serde(rename = "todo!")]
+    TodoMacro,
+    #[serde(rename = "unimplemented!")]
+    Unimplemented,
+    #[serde(rename = "unreachable!")]
+    Unreachable,
+    #[serde(rename = "panic!")]
+    Panic,
+}
+
+/// Item without references outside of it, dimmed and listed on the dead code page
+#[derive(Debug, Serialize, Clone)]
+pub struct UnusedItem {
+    pub kind: ItemKind,
+    pub name: String,
+    /// Line of the name
+    pub line: u32,
+    /// Lines of the whole item with its docs and attributes
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ItemKind {
+    #[serde(rename = "fn")]
+    Fn,
+    #[serde(rename = "struct")]
+    Struct,
+    #[serde(rename = "const")]
+    Const,
+    #[serde(rename = "trait method")]
+    TraitMethod,
 }
 
 #[derive(Debug, Serialize)]
@@ -31,22 +96,65 @@ impl From<ide::LineCol> for LineCol {
 
 #[derive(Debug, Serialize, Clone)]
 pub struct Navigation {
-    #[serde(rename = "def")]
-    pub definition: JumpDestination,
+    #[serde(rename = "defs")]
+    pub definitions: Vec<JumpDestination>,
     #[serde(rename = "refs")]
     pub references: Vec<JumpDestination>,
     pub from: JumpDestination,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calls: Option<CallHierarchy>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CallHierarchy {
+    #[serde(rename = "in")]
+    pub incoming: Vec<CallJump>,
+    #[serde(rename = "out")]
+    pub outgoing: Vec<CallJump>,
 }
 
-#[serde_as]
 #[derive(Debug, Serialize, Clone)]
+pub struct CallJump {
+    pub name: String,
+    pub count: usize,
+    #[serde(flatten)]
+    pub destination: JumpDestination,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
 pub struct JumpDestination {
     pub file: String,
     #[serde(rename = "loc")]
     pub location: JumpLocation,
+    #[serde(rename = "cat", skip_serializing_if = "Option::is_none")]
+    pub category: Option<ReferenceKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Text of the button instead of `file:line`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+    Read,
+    Write,
+    Import,
+}
+
+impl From<ReferenceCategory> for ReferenceKind {
+    fn from(value: ReferenceCategory) -> Self {
+        match value {
+            ReferenceCategory::Read => Self::Read,
+            ReferenceCategory::Write => Self::Write,
+            ReferenceCategory::Import => Self::Import,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
 pub struct JumpLocation {
     pub line: u32,
 }
@@ -56,10 +164,34 @@ impl JumpDestination {
         Self {
             file,
             location: JumpLocation::from_focus(focus, finder),
+            category: None,
+            url: None,
+            title: None,
         }
     }
     pub fn new(file: String, location: JumpLocation) -> Self {
-        Self { file, location }
+        Self {
+            file,
+            location,
+            category: None,
+            url: None,
+            title: None,
+        }
+    }
+
+    pub fn with_url(mut self, url: String) -> Self {
+        self.url = Some(url);
+        self
+    }
+
+    pub fn with_title(mut self, title: String) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    pub fn with_category(mut self, category: Option<ReferenceKind>) -> Self {
+        self.category = category;
+        self
     }
 }
 
@@ -0,0 +1,563 @@
+use ide::LineIndex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use syntax::{
+    ast, match_ast, AstNode, SyntaxKind as SK, SyntaxNode, SyntaxToken, TextRange, TextSize,
+};
+
+use super::markdown::resolve_path;
+use crate::{
+    parser::FileInfo,
+    render::{ExternalCrates, HtmlToken, JumpDestination, JumpLocation, Navigation},
+};
+
+/// Usages of dependencies and features in syntax trees of rust files of the report,
+/// used to link entries of `Cargo.toml` files.
+#[derive(Debug, Default, Clone)]
+pub struct ManifestIndex {
+    crate_usages: HashMap<String, Vec<JumpDestination>>,
+    feature_usages: HashMap<String, Vec<JumpDestination>>,
+}
+
+impl ManifestIndex {
+    pub fn new(files: &BTreeMap<String, FileInfo>) -> Self {
+        let dependencies: HashSet<String> = files
+            .values()
+            .filter(|file| is_manifest(&file.name))
+            .flat_map(|file| parse_manifest(&file.content))
+            .filter_map(|entry| match entry.kind {
+                EntryKind::Dependency { name, .. } => Some(crate_ident(&name)),
+                _ => None,
+            })
+            .collect();
+
+        let mut index = Self::default();
+        let rust_files = files
+            .values()
+            .filter(|file| file.path.extension().map_or(false, |e| e == "rs"));
+        for file in rust_files {
+            let root = ast::SourceFile::parse(&file.content).tree();
+            let finder = LineIndex::new(&file.content);
+            let by_lines = |usages: Vec<(String, TextSize)>| {
+                let mut usages: Vec<_> = usages
+                    .into_iter()
+                    .map(|(name, offset)| (finder.line_col(offset).line + 1, name))
+                    .collect();
+                usages.sort_unstable();
+                usages.dedup();
+                usages
+            };
+            let site = |line| JumpDestination::new(file.name.clone(), JumpLocation { line });
+            for (line, feature) in by_lines(feature_usages(root.syntax())) {
+                index
+                    .feature_usages
+                    .entry(feature)
+                    .or_default()
+                    .push(site(line));
+            }
+            for (line, name) in by_lines(crate_usages(root.syntax())) {
+                if dependencies.contains(&name) {
+                    index.crate_usages.entry(name).or_default().push(site(line));
+                }
+            }
+        }
+        index
+    }
+}
+
+pub fn is_manifest(file_name: &str) -> bool {
+    file_name.ends_with("/Cargo.toml")
+}
+
+/// Adds navigation to dependencies, features and paths of `Cargo.toml`.
+/// Dependencies which manifests are not in the report are linked like other external crates.
+pub fn link_manifest(
+    tokens: &mut [HtmlToken],
+    file_name: &str,
+    content: &str,
+    index: &ManifestIndex,
+    known_files: &HashSet<String>,
+    external_crates: &ExternalCrates,
+) {
+    let file_jump = |path: &str| {
+        resolve_path(file_name, path)
+            .filter(|file| known_files.contains(file))
+            .map(|file| JumpDestination::new(file, JumpLocation { line: 1 }))
+    };
+    let navigations: HashMap<TextRange, Navigation> = parse_manifest(content)
+        .into_iter()
+        .filter_map(|entry| {
+            let from =
+                JumpDestination::new(file_name.to_string(), JumpLocation { line: entry.line });
+            let (definitions, references) = match entry.kind {
+                EntryKind::Dependency {
+                    name,
+                    package,
+                    version,
+                    path,
+                } => {
+                    let package = package.unwrap_or_else(|| name.clone());
+                    let definition = path
+                        .and_then(|path| file_jump(&format!("{path}/Cargo.toml")))
+                        .unwrap_or_else(|| {
+                            external_crates.dependency_jump(&package, version.as_deref())
+                        });
+                    let usages = index.crate_usages.get(&crate_ident(&name));
+                    (vec![definition], usages.cloned().unwrap_or_default())
+                }
+                EntryKind::Crate { path, package } => {
+                    let definition = file_jump(&format!("{path}/Cargo.toml")).or_else(|| {
+                        package.map(|package| external_crates.dependency_jump(&package, None))
+                    })?;
+                    (vec![definition], vec![])
+                }
+                EntryKind::Source(path) => (vec![file_jump(&path)?], vec![]),
+                EntryKind::Feature(name) => {
+                    let usages = index.feature_usages.get(&name);
+                    (vec![from.clone()], usages.cloned().unwrap_or_default())
+                }
+            };
+            let navigation = Navigation {
+                definitions,
+                references,
+                from,
+                calls: None,
+            };
+            Some((entry.range, navigation))
+        })
+        .collect();
+    for token in tokens.iter_mut() {
+        if let Some(navigation) = navigations.get(&token.range) {
+            token.navigation = Some(navigation.clone());
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Entry {
+    line: u32,
+    /// Range of the key or the string, the same as the one of the highlighted token
+    range: TextRange,
+    kind: EntryKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum EntryKind {
+    Dependency {
+        name: String,
+        package: Option<String>,
+        version: Option<String>,
+        path: Option<String>,
+    },
+    /// Directory of a crate: `path` of a dependency with the name of its package
+    /// or a member of the workspace
+    Crate {
+        path: String,
+        package: Option<String>,
+    },
+    /// Source file: `path` of a target or `build` script
+    Source(String),
+    Feature(String),
+}
+
+fn parse_manifest(content: &str) -> Vec<Entry> {
+    let member = |path: &str| EntryKind::Crate {
+        path: path.to_string(),
+        package: None,
+    };
+    let mut entries = vec![];
+    let mut table = String::new();
+    let mut in_members = false;
+    let mut offset = 0;
+    for (number, line) in content.split('\n').enumerate() {
+        let line_offset = offset;
+        offset += line.len() + 1;
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let entry = |(start, end): (usize, usize), kind| Entry {
+            line: number as u32 + 1,
+            range: TextRange::new(
+                ((line_offset + start) as u32).into(),
+                ((line_offset + end) as u32).into(),
+            ),
+            kind,
+        };
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+
+        if in_members {
+            for (start, end, path) in strings_in_line(line, 0) {
+                entries.push(entry((start, end), member(path)));
+            }
+            in_members = !line.contains(']');
+            continue;
+        }
+        if trimmed.starts_with('[') {
+            table = trimmed
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            // [dependencies.<name>]
+            if let Some((parent, name)) = table.rsplit_once('.') {
+                if is_dependency_table(parent) {
+                    let end = line[indent..]
+                        .find(']')
+                        .map_or(line.len(), |end| indent + end + 1);
+                    let kind = EntryKind::Dependency {
+                        name: name.to_string(),
+                        package: None,
+                        version: None,
+                        path: None,
+                    };
+                    entries.push(entry((indent, end), kind));
+                }
+            }
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        let (key, _) = match line.split_once('=') {
+            Some(key_value) => key_value,
+            None => continue,
+        };
+        let value_start = key.len() + 1;
+        // dotted keys (e.g. `serde.workspace = true`) are highlighted by segments
+        let key = key.trim().split('.').next().unwrap_or_default();
+        let key_range = (indent, indent + key.len());
+        let key = key.trim_matches(['"', '\'']);
+
+        match table.as_str() {
+            table if is_dependency_table(table) => {
+                let path = find_field(line, value_start, "path");
+                let package = find_field(line, value_start, "package")
+                    .map(|(_, _, package)| package.to_string());
+                // `name = "1.0"` or `name = { version = "1.0", .. }`
+                let version = string_value(line, value_start)
+                    .or_else(|| find_field(line, value_start, "version"))
+                    .map(|(_, _, version)| version.to_string());
+                let kind = EntryKind::Dependency {
+                    name: key.to_string(),
+                    package: package.clone(),
+                    version,
+                    path: path.map(|(_, _, path)| path.to_string()),
+                };
+                entries.push(entry(key_range, kind));
+                if let Some((start, end, path)) = path {
+                    let kind = EntryKind::Crate {
+                        path: path.to_string(),
+                        package: Some(package.unwrap_or_else(|| key.to_string())),
+                    };
+                    entries.push(entry((start, end), kind));
+                }
+            }
+            table
+                if key == "path"
+                    && table
+                        .rsplit_once('.')
+                        .map_or(false, |(parent, _)| is_dependency_table(parent)) =>
+            {
+                if let Some((start, end, path)) = string_value(line, value_start) {
+                    // `package` may follow on the next lines, the name of the table is enough
+                    let kind = EntryKind::Crate {
+                        path: path.to_string(),
+                        package: table.rsplit_once('.').map(|(_, name)| name.to_string()),
+                    };
+                    entries.push(entry((start, end), kind));
+                }
+            }
+            "features" => {
+                entries.push(entry(key_range, EntryKind::Feature(key.to_string())));
+            }
+            "lib" | "bin" | "test" | "bench" | "example" if key == "path" => {
+                if let Some((start, end, path)) = string_value(line, value_start) {
+                    entries.push(entry((start, end), EntryKind::Source(path.to_string())));
+                }
+            }
+            "package" if key == "build" => {
+                if let Some((start, end, path)) = string_value(line, value_start) {
+                    entries.push(entry((start, end), EntryKind::Source(path.to_string())));
+                }
+            }
+            "workspace" if key == "members" => {
+                for (start, end, path) in strings_in_line(line, value_start) {
+                    entries.push(entry((start, end), member(path)));
+                }
+                in_members = !line[value_start..].contains(']');
+            }
+            _ => (),
+        }
+    }
+    entries
+}
+
+fn is_dependency_table(table: &str) -> bool {
+    matches!(
+        table,
+        "dependencies" | "dev-dependencies" | "build-dependencies" | "workspace.dependencies"
+    ) || table.ends_with(".dependencies")
+        || table.ends_with(".dev-dependencies")
+        || table.ends_with(".build-dependencies")
+}
+
+fn crate_ident(name: &str) -> String {
+    name.replace('-', "_")
+}
+
+/// Finds `field = "value"` of an inline table, returns the range of the string and its content.
+fn find_field<'a>(line: &'a str, from: usize, field: &str) -> Option<(usize, usize, &'a str)> {
+    let mut search = from;
+    while let Some(position) = line[search..].find(field) {
+        let start = search + position;
+        search = start + field.len();
+        let is_key_start = line[..start]
+            .chars()
+            .next_back()
+            .map_or(true, |c| c == '{' || c == ',' || c.is_whitespace());
+        if is_key_start && line[search..].trim_start().starts_with('=') {
+            let value_start = search + line[search..].find('=')? + 1;
+            return string_value(line, value_start);
+        }
+    }
+    None
+}
+
+/// Returns the string which starts after whitespaces at `from`.
+fn string_value(line: &str, from: usize) -> Option<(usize, usize, &str)> {
+    let start = from + line[from..].len() - line[from..].trim_start().len();
+    string_at(line, start)
+}
+
+fn string_at(line: &str, start: usize) -> Option<(usize, usize, &str)> {
+    let quote = line[start..]
+        .chars()
+        .next()
+        .filter(|c| *c == '"' || *c == '\'')?;
+    let end = start + 1 + line[start + 1..].find(quote)?;
+    Some((start, end + 1, &line[start + 1..end]))
+}
+
+fn strings_in_line(line: &str, from: usize) -> Vec<(usize, usize, &str)> {
+    let mut strings = vec![];
+    let mut search = from;
+    while let Some(position) = line[search..].find('"') {
+        match string_at(line, search + position) {
+            Some(string) => {
+                search = string.1;
+                strings.push(string);
+            }
+            None => break,
+        }
+    }
+    strings
+}
+
+/// Names of features in `feature = "..."` of attributes and `cfg!` calls.
+fn feature_usages(root: &SyntaxNode) -> Vec<(String, TextSize)> {
+    let attrs = root
+        .descendants()
+        .filter_map(ast::Attr::cast)
+        .map(|attr| attr.syntax().clone());
+    let cfg_calls = root
+        .descendants()
+        .filter_map(ast::MacroCall::cast)
+        .filter(|call| call.path().map_or(false, |path| path.to_string() == "cfg"))
+        .filter_map(|call| call.token_tree())
+        .map(|tree| tree.syntax().clone());
+    let mut features = vec![];
+    for node in attrs.chain(cfg_calls) {
+        let tokens = significant_tokens(&node);
+        for window in tokens.windows(3) {
+            let (name, eq, value) = (&window[0], &window[1], &window[2]);
+            if name.kind() != SK::IDENT
+                || name.text() != "feature"
+                || eq.kind() != SK::EQ
+                || value.kind() != SK::STRING
+            {
+                continue;
+            }
+            let feature = value
+                .text()
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'));
+            if let Some(feature) = feature {
+                features.push((feature.to_string(), value.text_range().start()));
+            }
+        }
+    }
+    features
+}
+
+/// First segments of paths and `use` trees, `extern crate` names
+/// and `name::` in the arguments of macro calls.
+fn crate_usages(root: &SyntaxNode) -> Vec<(String, TextSize)> {
+    let mut roots = vec![];
+    for node in root.descendants() {
+        match_ast! {
+            match node {
+                ast::Path(path) => {
+                    let name = path.segment().and_then(|segment| segment.name_ref());
+                    if let Some(name) = name.filter(|_| is_path_root(&path)) {
+                        roots.push((name.text().to_string(), name.syntax().text_range().start()));
+                    }
+                },
+                ast::ExternCrate(it) => {
+                    if let Some(name) = it.name_ref() {
+                        roots.push((name.text().to_string(), name.syntax().text_range().start()));
+                    }
+                },
+                // arguments of macro calls are not parsed
+                ast::TokenTree(tree) => {
+                    if node.parent().map_or(false, |parent| parent.kind() == SK::MACRO_CALL) {
+                        roots.extend(token_tree_roots(tree.syntax()));
+                    }
+                },
+                _ => (),
+            }
+        }
+    }
+    roots
+}
+
+fn is_path_root(path: &ast::Path) -> bool {
+    if path.qualifier().is_some() {
+        return false;
+    }
+    match path.syntax().ancestors().find_map(ast::UseTree::cast) {
+        // `use a::{b::c}` continues the path of the outer tree
+        Some(tree) => !tree
+            .syntax()
+            .ancestors()
+            .skip(1)
+            .filter_map(ast::UseTree::cast)
+            .any(|outer| outer.path().is_some()),
+        // only the qualifier of a longer path
+        None => path
+            .syntax()
+            .parent()
+            .map_or(false, |parent| parent.kind() == SK::PATH),
+    }
+}
+
+fn token_tree_roots(tree: &SyntaxNode) -> Vec<(String, TextSize)> {
+    let tokens = significant_tokens(tree);
+    let text_at = |index: usize| tokens.get(index).map_or("", |token| token.text());
+    // `::` may come as two tokens
+    let colons_at = |index: usize| {
+        text_at(index) == "::" || (text_at(index) == ":" && text_at(index + 1) == ":")
+    };
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(index, token)| {
+            token.kind() == SK::IDENT
+                && colons_at(index + 1)
+                && (*index == 0 || !matches!(text_at(index - 1), ":" | "::" | "." | "$"))
+        })
+        .map(|(_, token)| (token.text().to_string(), token.text_range().start()))
+        .collect()
+}
+
+fn significant_tokens(node: &SyntaxNode) -> Vec<SyntaxToken> {
+    node.descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| !token.kind().is_trivia())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let content = r#"[package]
+build = "build.rs"
+
+[dependencies]
+syn = { version = "1.0", features = ["full"] }
+ide = { path = "../ra/ide", package = "ra_ap_ide" }
+
+[features]
+fast = []
+
+[[bin]]
+path = "src/main.rs"
+"#;
+        let entries: Vec<_> = parse_manifest(content)
+            .into_iter()
+            .map(|entry| (entry.line, &content[entry.range], entry.kind))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (2, "\"build.rs\"", EntryKind::Source("build.rs".into())),
+                (
+                    5,
+                    "syn",
+                    EntryKind::Dependency {
+                        name: "syn".into(),
+                        package: None,
+                        version: Some("1.0".into()),
+                        path: None
+                    }
+                ),
+                (
+                    6,
+                    "ide",
+                    EntryKind::Dependency {
+                        name: "ide".into(),
+                        package: Some("ra_ap_ide".into()),
+                        version: None,
+                        path: Some("../ra/ide".into())
+                    }
+                ),
+                (
+                    6,
+                    "\"../ra/ide\"",
+                    EntryKind::Crate {
+                        path: "../ra/ide".into(),
+                        package: Some("ra_ap_ide".into())
+                    }
+                ),
+                (9, "fast", EntryKind::Feature("fast".into())),
+                (
+                    12,
+                    "\"src/main.rs\"",
+                    EntryKind::Source("src/main.rs".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_usages() {
+        let text = r##"
+use serde::Serialize;
+use std::{fmt, io::Write};
+extern crate tera;
+#[cfg(all(feature = "a", not(feature="b")))]
+#[target_feature(enable = "avx2")]
+#[cfg(target_feature = "sse2")]
+fn f() {
+    let s = "#[cfg(feature = \"c\")] use fake::x";
+    /* #[cfg(feature = "d")] other::x */
+    // syn::x
+    let v = regex::Regex::new("x");
+    println!("{}", serde_json::to_string(&v));
+    m!(reuse tera);
+    if cfg!(feature = "e") {}
+}
+"##;
+        let file = ast::SourceFile::parse(text).tree();
+        let names = |usages: Vec<(String, TextSize)>| -> Vec<String> {
+            usages.into_iter().map(|(name, _)| name).collect()
+        };
+        assert_eq!(names(feature_usages(file.syntax())), vec!["a", "b", "e"]);
+        assert_eq!(
+            names(crate_usages(file.syntax())),
+            vec!["serde", "std", "tera", "regex", "serde_json"]
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+use std::collections::HashSet;
+
+/// Renders markdown file as HTML. Relative links to files of the report
+/// are turned into report navigation, raw HTML is shown as text.
+pub fn render_markdown(content: &str, file_name: &str, known_files: &HashSet<String>) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    // links cannot be nested, so it is enough to remember the last one
+    let mut in_file_link = false;
+    let events = Parser::new_ext(content, options).map(|event| match event {
+        Event::Start(Tag::Link(kind, dest, title)) if is_script_url(&dest) => {
+            Event::Start(Tag::Link(kind, "".into(), title))
+        }
+        Event::Start(Tag::Link(_, ref dest, _)) => {
+            match resolve_link(file_name, dest).filter(|(file, _)| known_files.contains(file)) {
+                Some((file, line)) => {
+                    in_file_link = true;
+                    let file = html_escape::encode_double_quoted_attribute(&file);
+                    let line = line.map(|line| line.to_string()).unwrap_or_default();
+                    let link = format!(
+                        "<a class=\"file-link\" data-file=\"{file}\" data-line=\"{line}\" href=\"#\">"
+                    );
+                    Event::Html(link.into())
+                }
+                None => event,
+            }
+        }
+        Event::End(Tag::Link(..)) if in_file_link => {
+            in_file_link = false;
+            Event::Html("</a>".into())
+        }
+        // markdown of the included crates is not trusted
+        Event::Html(html) => Event::Text(html),
+        _ => event,
+    });
+    let mut result = String::new();
+    html::push_html(&mut result, events);
+    result
+}
+
+fn is_script_url(dest: &str) -> bool {
+    let dest = dest.trim_start().to_ascii_lowercase();
+    dest.starts_with("javascript:") || dest.starts_with("vbscript:") || dest.starts_with("data:")
+}
+
+/// Resolves relative link of the markdown file into the name of the report file
+/// and the line from `#L<line>` fragment.
+fn resolve_link(file_name: &str, dest: &str) -> Option<(String, Option<u32>)> {
+    if dest.is_empty()
+        || dest.contains("://")
+        || dest.starts_with(['#', '/'])
+        || dest.starts_with("mailto:")
+    {
+        return None;
+    }
+    let (path, fragment) = dest.split_once('#').unwrap_or((dest, ""));
+    let line = fragment
+        .strip_prefix('L')
+        .and_then(|line| line.parse().ok());
+    Some((resolve_path(file_name, path)?, line))
+}
+
+/// Resolves path relative to the directory of the report file.
+pub fn resolve_path(file_name: &str, path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = file_name.split('/').collect();
+    parts.pop();
+    for part in path.split('/') {
+        match part {
+            "" | "." => (),
+            // report root cannot be left
+            ".." if parts.len() <= 1 => return None,
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_link() {
+        for (file_name, dest, expected) in [
+            ("p/README.md", "src/lib.rs", Some(("p/src/lib.rs", None))),
+            (
+                "p/docs/a.md",
+                "../src/lib.rs#L10",
+                Some(("p/src/lib.rs", Some(10))),
+            ),
+            ("p/docs/a.md", "./b.md#usage", Some(("p/docs/b.md", None))),
+            ("p/README.md", "../other/lib.rs", None),
+            ("p/README.md", "https://docs.rs", None),
+            ("p/README.md", "#usage", None),
+        ] {
+            let expected = expected.map(|(file, line)| (file.to_string(), line));
+            assert_eq!(resolve_link(file_name, dest), expected);
+        }
+    }
+
+    #[test]
+    fn test_raw_html_is_escaped() {
+        let html = render_markdown(
+            "<script>alert(1)</script>\n\nSee <img src=x onerror=alert(1)> and [lib](src/lib.rs) [x](JavaScript:alert(1))\n",
+            "p/README.md",
+            &HashSet::from(["p/src/lib.rs".to_string()]),
+        );
+        assert!(!html.contains("<script"), "{html}");
+        assert!(!html.contains("<img"), "{html}");
+        assert!(!html.contains("alert(1)\""), "{html}");
+        assert!(html.contains("&lt;script&gt;"), "{html}");
+        assert!(html.contains("data-file=\"p/src/lib.rs\""), "{html}");
+    }
+}
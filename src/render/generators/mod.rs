@@ -1,7 +1,19 @@
 mod html;
 mod html_token;
+mod manifest;
+mod markdown;
+mod outline;
+mod pages;
 mod report;
 
-pub use html::HtmlGenerator;
-pub use html_token::{HtmlToken, JumpDestination, JumpLocation, Navigation};
-pub use report::{MyPath, ReportGenerator};
+pub use html::{FileSummary, HtmlGenerator, RenderedFile, TaskSite, UnsafeSite};
+pub use html_token::{
+    CallHierarchy, CallJump, HtmlToken, ItemKind, JumpDestination, JumpLocation, MacroExpansion,
+    Navigation, ReferenceKind, TaskKind, UnsafeKind, UnusedItem,
+};
+pub use markdown::render_markdown;
+pub use outline::render_module_tree;
+pub use pages::{
+    dead_code_page, metrics_page, overview_page, tasks_page, tests_page, unsafe_page, ReportPage,
+};
+pub use report::{CrateRoot, MyPath, ProjectInfo, ReportContent, ReportGenerator};
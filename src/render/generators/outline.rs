@@ -0,0 +1,86 @@
+use serde::Serialize;
+use tera::Context;
+
+use crate::render::{static_files, JumpDestination, ModuleKind, ModuleNode, OutlineNode};
+
+/// Module of the `module_tree` macro
+#[derive(Serialize)]
+struct ModuleEntry<'a> {
+    name: &'a str,
+    kind: &'static str,
+    link: Option<FileLink<'a>>,
+    items: Vec<ItemEntry<'a>>,
+    children: Vec<ModuleEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct ItemEntry<'a> {
+    name: &'a str,
+    kind: &'static str,
+    visibility: &'static str,
+    link: Option<FileLink<'a>>,
+}
+
+/// Line of a file of the report, items of dependencies are not linked
+#[derive(Serialize)]
+struct FileLink<'a> {
+    file: &'a str,
+    line: u32,
+}
+
+/// Symbols of the file shown in the outline mode of the left panel
+pub fn render_outline(file_name: &str, nodes: &[OutlineNode]) -> Result<String, anyhow::Error> {
+    if nodes.is_empty() {
+        return Ok(String::new());
+    }
+    let mut context = Context::new();
+    context.insert("file", file_name);
+    context.insert("nodes", nodes);
+    let result = static_files::templates::render("outline.html", &context)?;
+    Ok(result)
+}
+
+/// Crate and module hierarchy shown in the modules mode of the left panel
+pub fn render_module_tree(modules: &[ModuleNode]) -> Result<String, anyhow::Error> {
+    let modules: Vec<_> = modules.iter().map(module_entry).collect();
+    let mut context = Context::new();
+    context.insert("modules", &modules);
+    let result = static_files::templates::render("modules.html", &context)?;
+    Ok(result)
+}
+
+fn module_entry(module: &ModuleNode) -> ModuleEntry {
+    let kind = match module.kind {
+        ModuleKind::CrateRoot => "crate",
+        ModuleKind::File => "mod",
+        ModuleKind::Path => "#[path] mod",
+        ModuleKind::Inline => "inline mod",
+        ModuleKind::Block => "block",
+    };
+    ModuleEntry {
+        name: &module.name,
+        kind,
+        link: file_link(module.jump.as_ref()),
+        items: module
+            .items
+            .iter()
+            .map(|item| ItemEntry {
+                name: &item.name,
+                kind: item.kind,
+                visibility: item.visibility,
+                link: file_link(item.jump.as_ref()),
+            })
+            .collect(),
+        children: module.children.iter().map(module_entry).collect(),
+    }
+}
+
+fn file_link(jump: Option<&JumpDestination>) -> Option<FileLink> {
+    match jump {
+        Some(jump) if jump.url.is_none() => Some(FileLink {
+            file: &jump.file,
+            line: jump.location.line,
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,239 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tera::Context;
+
+use super::{FileSummary, ProjectInfo, TaskSite, UnsafeSite, UnusedItem};
+use crate::render::{static_files, FunctionMetrics, RunCommand, RunKind};
+
+/// Directory of the tree with the pages aggregated over the whole report
+pub const REPORTS_ROOT: &str = "@reports";
+
+/// Page of the report which is shown like a file
+pub struct ReportPage {
+    /// Name in the tree, it is placed in the `REPORTS_ROOT` directory
+    pub name: String,
+    pub html: String,
+}
+
+impl ReportPage {
+    fn new(name: &str, html: String) -> Self {
+        Self {
+            name: format!("{REPORTS_ROOT}/{name}"),
+            html,
+        }
+    }
+}
+
+/// Metadata and statistics of the project followed by its readme
+pub fn overview_page(
+    project: &ProjectInfo,
+    readme: Option<&str>,
+) -> Result<ReportPage, anyhow::Error> {
+    let mut context = Context::new();
+    context.insert("project", project);
+    context.insert("readme", &readme);
+    let html = static_files::templates::render("overview.html", &context)?;
+    Ok(ReportPage::new("overview", html))
+}
+
+/// Line of a file on a page which lists lines grouped by files
+#[derive(Serialize)]
+struct PageSite<'a, K> {
+    line: usize,
+    kinds: Vec<K>,
+    code: &'a str,
+}
+
+#[derive(Serialize)]
+struct SiteFile<'a, K> {
+    name: &'a str,
+    sites: Vec<PageSite<'a, K>>,
+}
+
+#[derive(Serialize)]
+struct KindCount<K> {
+    kind: K,
+    count: usize,
+}
+
+/// Context of a page with the sites of the files and the counts of their kinds,
+/// `file_sites` selects the sites of a file and `site` converts one of them
+fn sites_context<'a, S: 'a, K: Serialize + Ord + Copy>(
+    summaries: &'a BTreeMap<String, FileSummary>,
+    file_sites: impl Fn(&'a FileSummary) -> &'a [S],
+    site: impl Fn(&'a S) -> PageSite<'a, K>,
+) -> Context {
+    let files: Vec<_> = summaries
+        .iter()
+        .map(|(name, summary)| SiteFile {
+            name,
+            sites: file_sites(summary).iter().map(&site).collect(),
+        })
+        .filter(|file| !file.sites.is_empty())
+        .collect();
+    let mut counts = BTreeMap::<K, usize>::new();
+    for site in files.iter().flat_map(|file| &file.sites) {
+        for kind in &site.kinds {
+            *counts.entry(*kind).or_default() += 1;
+        }
+    }
+    let kinds: Vec<_> = counts
+        .into_iter()
+        .map(|(kind, count)| KindCount { kind, count })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("files", &files);
+    context.insert("kinds", &kinds);
+    context.insert(
+        "total",
+        &files.iter().map(|file| file.sites.len()).sum::<usize>(),
+    );
+    context
+}
+
+pub fn unsafe_page(summaries: &BTreeMap<String, FileSummary>) -> Result<ReportPage, anyhow::Error> {
+    let context = sites_context(
+        summaries,
+        |summary| &summary.unsafe_sites,
+        |site: &UnsafeSite| PageSite {
+            line: site.line,
+            kinds: site.kinds.clone(),
+            code: &site.code,
+        },
+    );
+    let html = static_files::templates::render("unsafe.html", &context)?;
+    Ok(ReportPage::new("unsafe", html))
+}
+
+pub fn tasks_page(summaries: &BTreeMap<String, FileSummary>) -> Result<ReportPage, anyhow::Error> {
+    let context = sites_context(
+        summaries,
+        |summary| &summary.tasks,
+        |site: &TaskSite| PageSite {
+            line: site.line,
+            kinds: site.kinds.clone(),
+            code: &site.code,
+        },
+    );
+    let html = static_files::templates::render("tasks.html", &context)?;
+    Ok(ReportPage::new("tasks", html))
+}
+
+pub fn dead_code_page(
+    summaries: &BTreeMap<String, FileSummary>,
+) -> Result<ReportPage, anyhow::Error> {
+    let context = sites_context(
+        summaries,
+        |summary| &summary.unused_items,
+        |item: &UnusedItem| PageSite {
+            line: item.line as usize,
+            kinds: vec![item.kind],
+            code: &item.name,
+        },
+    );
+    let html = static_files::templates::render("dead_code.html", &context)?;
+    Ok(ReportPage::new("dead-code", html))
+}
+
+#[derive(Serialize)]
+struct TestModule<'a> {
+    name: String,
+    tests: Vec<TestEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct TestEntry<'a> {
+    file: &'a str,
+    line: u32,
+    name: &'a str,
+    kind: RunKind,
+    ignored: bool,
+    command: &'a RunCommand,
+}
+
+pub fn tests_page(summaries: &BTreeMap<String, FileSummary>) -> Result<ReportPage, anyhow::Error> {
+    let mut modules = BTreeMap::<String, Vec<TestEntry>>::new();
+    let mut counts = BTreeMap::<RunKind, usize>::new();
+    let tests = summaries.iter().flat_map(|(file, summary)| {
+        summary
+            .runnables
+            .iter()
+            .filter(|runnable| {
+                matches!(
+                    runnable.kind,
+                    RunKind::Test | RunKind::Bench | RunKind::DocTest
+                )
+            })
+            .map(move |runnable| (file, runnable))
+    });
+    for (file, runnable) in tests {
+        let (module, name) = runnable
+            .name
+            .rsplit_once("::")
+            .unwrap_or(("", runnable.name.as_str()));
+        let module = match &runnable.package {
+            Some(package) if module.is_empty() => package.clone(),
+            Some(package) => format!("{package}::{module}"),
+            None => module.to_string(),
+        };
+        *counts.entry(runnable.kind).or_default() += 1;
+        modules.entry(module).or_default().push(TestEntry {
+            file,
+            line: runnable.line,
+            name,
+            kind: runnable.kind,
+            ignored: runnable.ignored,
+            command: &runnable.command,
+        });
+    }
+    let modules: Vec<_> = modules
+        .into_iter()
+        .map(|(name, tests)| TestModule { name, tests })
+        .collect();
+    let counts: Vec<_> = counts
+        .into_iter()
+        .map(|(kind, count)| KindCount { kind, count })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("modules", &modules);
+    context.insert("kinds", &counts);
+    let html = static_files::templates::render("tests.html", &context)?;
+    Ok(ReportPage::new("tests", html))
+}
+
+#[derive(Serialize)]
+struct FunctionEntry<'a> {
+    file: &'a str,
+    #[serde(flatten)]
+    metrics: &'a FunctionMetrics,
+}
+
+/// Functions of all files, the most complex ones go first
+pub fn metrics_page(
+    summaries: &BTreeMap<String, FileSummary>,
+) -> Result<ReportPage, anyhow::Error> {
+    let mut functions: Vec<_> = summaries
+        .iter()
+        .flat_map(|(file, summary)| {
+            summary
+                .metrics
+                .iter()
+                .map(move |metrics| FunctionEntry { file, metrics })
+        })
+        .collect();
+    functions.sort_by_key(|function| std::cmp::Reverse(function.metrics.complexity));
+
+    let mut context = Context::new();
+    context.insert(
+        "hotspots",
+        &functions
+            .iter()
+            .filter(|function| function.metrics.hotspot)
+            .count(),
+    );
+    context.insert("functions", &functions);
+    let html = static_files::templates::render("metrics.html", &context)?;
+    Ok(ReportPage::new("metrics", html))
+}
@@ -1,4 +1,12 @@
-use crate::render::{compress_html, static_files};
+use super::ReportPage;
+use crate::{
+    coverage::CoverageTotals,
+    git::Churn,
+    metadata::{LanguageStats, Package},
+    render::{compress_html, static_files},
+    theme::Themes,
+};
+use serde::Serialize;
 use std::collections::HashMap;
 use tera::Context;
 
@@ -20,21 +28,123 @@ pub struct MyDir {
     children: Vec<MyDir>,
 }
 
+/// Badges shown after the names of files and directories in the tree, by their paths
+struct TreeBadges {
+    coverage: HashMap<String, CoverageBadge>,
+    churn: HashMap<String, ChurnBadge>,
+}
+
+/// File or directory of the tree, rendered by the `tree_item` macro
+#[derive(Serialize)]
+struct TreeItem {
+    name: String,
+    /// Name of the file, directories end with `/`
+    path: String,
+    is_file: bool,
+    coverage: Option<CoverageBadge>,
+    churn: Option<ChurnBadge>,
+    children: Vec<TreeItem>,
+}
+
+#[derive(Clone, Serialize)]
+struct CoverageBadge {
+    percent: usize,
+    level: &'static str,
+    covered: usize,
+    total: usize,
+}
+
+#[derive(Clone, Serialize)]
+struct ChurnBadge {
+    commits: usize,
+    /// From 1 to 4, relative to the most changed file
+    level: usize,
+    days: u32,
+}
+
 #[derive(Default)]
 pub struct ReportGenerator {}
 
+/// Metadata of the project available to the templates
+#[derive(Debug, Default, Serialize)]
+pub struct ProjectInfo {
+    pub name: String,
+    pub root: String,
+    /// Version of the generator
+    pub generator_version: &'static str,
+    pub rust_analyzer_version: String,
+    /// Time of the generation, UTC
+    pub generated_at: String,
+    /// Packages of the workspace, the one of the root manifest goes first
+    pub packages: Vec<Package>,
+    pub crate_roots: Vec<CrateRoot>,
+    /// Files and lines of the project by languages
+    pub languages: Vec<LanguageStats>,
+    pub files: usize,
+    pub lines: usize,
+}
+
+/// Root file of a crate of the report
+#[derive(Debug, Serialize)]
+pub struct CrateRoot {
+    pub name: String,
+    pub file: String,
+    pub line: u32,
+}
+
+/// Rendered files and pages with the data shown around them
+#[derive(Default)]
+pub struct ReportContent {
+    pub filenames: Vec<MyPath>,
+    pub files: HashMap<String, String>,
+    pub pages: Vec<ReportPage>,
+    pub readme: Option<String>,
+    /// Landing page of the report, shown until a file is selected
+    pub overview: String,
+    pub modules: String,
+    /// Line coverage of the files, directories show the sum of their files
+    pub coverage: HashMap<String, CoverageTotals>,
+    /// Recent commits of the files and directories, by the same paths as the tree
+    pub churn: Churn,
+    pub themes: Themes,
+    pub project: ProjectInfo,
+}
+
 impl ReportGenerator {
     pub fn generate(
         &self,
-        filenames: Vec<MyPath>,
-        files: HashMap<String, String>,
-        dir: &str,
+        report: ReportContent,
         no_compress: bool,
-    ) -> String {
-        let tree = MyDir::from_paths(filenames, dir);
-        let tree = traverse(tree, "");
+    ) -> Result<String, anyhow::Error> {
+        let ReportContent {
+            mut filenames,
+            mut files,
+            pages,
+            readme,
+            overview,
+            modules,
+            coverage,
+            churn,
+            themes,
+            project,
+        } = report;
+        let badges = TreeBadges {
+            coverage: coverage_badges(&coverage),
+            churn: churn_badges(&churn),
+        };
+        // pages are shown as files of a separate tree after the sources
+        for page in pages {
+            filenames.push(MyPath::new(&page.name));
+            files.insert(page.name, page.html);
+        }
+        // every report root (project and included crates) is a separate tree
+        let tree = MyDir::from_paths(filenames)
+            .children
+            .into_iter()
+            .map(|root| traverse(root, "", &badges))
+            .collect::<Vec<TreeItem>>();
         let script = get_java_script();
-        let styles = static_files::css::STYLE.to_string();
+        let styles = format!("{}\n{}", *static_files::css::STYLE, themes.css);
         let files = save_files_in_html(files);
 
         let mut context = Context::new();
@@ -42,20 +152,24 @@ impl ReportGenerator {
         context.insert("script", &script);
         context.insert("styles", &styles);
         context.insert("files", &files);
-        let content = static_files::templates::TEMPLATES
-            .render("main.html", &context)
-            .unwrap();
+        context.insert("readme", &readme);
+        context.insert("overview", &overview);
+        context.insert("modules", &modules);
+        context.insert("project", &project);
+        context.insert("theme", &themes.default);
+        context.insert("themes", &themes.options);
+        let content = static_files::templates::render("main.html", &context)?;
         if no_compress {
-            content
+            Ok(content)
         } else {
-            compress_html(&content)
+            Ok(compress_html(&content))
         }
     }
 }
 
 impl MyDir {
-    pub fn from_paths(paths: Vec<MyPath>, top_dir_name: &str) -> MyDir {
-        let mut top = Self::new(top_dir_name);
+    pub fn from_paths(paths: Vec<MyPath>) -> MyDir {
+        let mut top = Self::new("");
         for path in paths.iter() {
             Self::build_tree(&mut top, &path.parts, 0);
         }
@@ -119,41 +233,84 @@ impl MyDir {
     }
 }
 
-fn traverse(mut tree: MyDir, prefix_path: &str) -> String {
+fn traverse(mut tree: MyDir, prefix_path: &str, badges: &TreeBadges) -> TreeItem {
     tree.sort();
-    let dirname = &tree.name;
-
-    if tree.is_file() {
-        let full_path = format!("{prefix_path}{dirname}");
-        format!(
-            r#"
-<label class="tnz-file-tree-item file">
-    <input class="tnz-file-tree-cb" type="radio" name="file" value="{full_path}">
-    <span class="tnz-file-tree-label">{dirname}</span>
-</label>
-"#
-        )
+    let path = if tree.is_file() {
+        format!("{prefix_path}{}", tree.name)
     } else {
-        let prefix_path = format!("{prefix_path}{dirname}/");
-        let result = tree
+        format!("{prefix_path}{}/", tree.name)
+    };
+    TreeItem {
+        is_file: tree.is_file(),
+        coverage: badges.coverage.get(&path).cloned(),
+        churn: badges.churn.get(&path).cloned(),
+        children: tree
             .children
             .into_iter()
-            .map(|d| traverse(d, &prefix_path))
-            .collect::<Vec<String>>()
-            .join("\n\n");
-        format!(
-            r#"
-<label class="tnz-file-tree-item dir">
-<input class="tnz-file-tree-cb" type="checkbox" value="{prefix_path}">
-
-<span class="tnz-file-tree-label">{dirname}</span>
-<div class="tnz-file-tree-branches">
-{result}
-</div>
-</label>
-"#
-        )
+            .map(|child| traverse(child, &path, badges))
+            .collect(),
+        name: tree.name,
+        path,
+    }
+}
+
+fn coverage_badges(coverage: &HashMap<String, CoverageTotals>) -> HashMap<String, CoverageBadge> {
+    let mut totals: HashMap<String, CoverageTotals> = HashMap::new();
+    for (file_name, file_totals) in coverage {
+        totals
+            .entry(file_name.clone())
+            .or_default()
+            .add(*file_totals);
+        for (index, _) in file_name.match_indices('/') {
+            let dir = &file_name[..=index];
+            totals.entry(dir.to_string()).or_default().add(*file_totals);
+        }
     }
+    totals
+        .into_iter()
+        .filter_map(|(path, totals)| {
+            let percent = totals.percent()?;
+            let level = match percent {
+                0..=49 => "low",
+                50..=79 => "medium",
+                _ => "high",
+            };
+            let badge = CoverageBadge {
+                percent,
+                level,
+                covered: totals.covered,
+                total: totals.total,
+            };
+            Some((path, badge))
+        })
+        .collect()
+}
+
+/// Heat map of the commits, relative to the most changed file
+fn churn_badges(churn: &Churn) -> HashMap<String, ChurnBadge> {
+    let max = churn
+        .commits
+        .iter()
+        .filter(|(path, _)| !path.ends_with('/'))
+        .map(|(_, commits)| *commits)
+        .max()
+        .unwrap_or_default();
+    churn
+        .commits
+        .iter()
+        .map(|(path, &commits)| {
+            let level = match commits * 4 / max.max(1) {
+                0 => 1,
+                level => level.min(4),
+            };
+            let badge = ChurnBadge {
+                commits,
+                level,
+                days: churn.days,
+            };
+            (path.clone(), badge)
+        })
+        .collect()
 }
 
 fn get_java_script() -> String {
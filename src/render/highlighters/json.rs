@@ -0,0 +1,47 @@
+use super::{
+    is_word_byte, lines, number_end, quoted_end, Highlighter, Span, BOOL, FIELD, KEYWORD, NUMBER,
+    STRING,
+};
+
+pub struct Json;
+
+impl Highlighter for Json {
+    fn highlight(&self, content: &str) -> Vec<Span> {
+        let mut spans = vec![];
+        for (offset, line) in lines(content) {
+            let bytes = line.as_bytes();
+            let mut i = 0;
+            while i < bytes.len() {
+                let start = i;
+                let class = match bytes[i] {
+                    b'"' => {
+                        i = quoted_end(line, i, true);
+                        if line[i..].trim_start().starts_with(':') {
+                            FIELD
+                        } else {
+                            STRING
+                        }
+                    }
+                    b'0'..=b'9' | b'-' if number_end(line, i).is_some() => {
+                        i = number_end(line, i).unwrap_or(i + 1);
+                        NUMBER
+                    }
+                    b if b.is_ascii_alphabetic() => {
+                        i += line[i..].bytes().take_while(|b| is_word_byte(*b)).count();
+                        match &line[start..i] {
+                            "true" | "false" => BOOL,
+                            "null" => KEYWORD,
+                            _ => continue,
+                        }
+                    }
+                    _ => {
+                        i += 1;
+                        continue;
+                    }
+                };
+                spans.push(Span::new(offset + start..offset + i, class));
+            }
+        }
+        spans
+    }
+}
@@ -0,0 +1,87 @@
+use super::{lines, Highlighter, Span, COMMENT, KEYWORD, LABEL, STRING, TYPE};
+
+pub struct Markdown;
+
+impl Highlighter for Markdown {
+    fn highlight(&self, content: &str) -> Vec<Span> {
+        let mut spans = vec![];
+        // start of the fenced code block which is not closed yet
+        let mut open_fence: Option<usize> = None;
+        for (offset, line) in lines(content) {
+            let indent = line.len() - line.trim_start().len();
+            let trimmed = &line[indent..];
+            let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+            match open_fence {
+                Some(start) if is_fence => {
+                    spans.push(Span::new(start..offset + line.len(), STRING));
+                    open_fence = None;
+                    continue;
+                }
+                Some(_) => continue,
+                None if is_fence => {
+                    open_fence = Some(offset + indent);
+                    continue;
+                }
+                None => (),
+            }
+
+            if trimmed.starts_with('#') {
+                spans.push(Span::new(offset + indent..offset + line.len(), KEYWORD));
+                continue;
+            }
+            if trimmed.starts_with('>') {
+                spans.push(Span::new(offset + indent..offset + line.len(), COMMENT));
+                continue;
+            }
+            let marker_len = list_marker_len(trimmed);
+            if marker_len > 0 {
+                spans.push(Span::new(
+                    offset + indent..offset + indent + marker_len,
+                    LABEL,
+                ));
+            }
+            highlight_inline(line, offset, indent + marker_len, &mut spans);
+        }
+        if let Some(start) = open_fence {
+            spans.push(Span::new(start..content.len(), STRING));
+        }
+        spans
+    }
+}
+
+/// Highlights code spans and targets of links.
+fn highlight_inline(line: &str, offset: usize, start: usize, spans: &mut Vec<Span>) {
+    let bytes = line.as_bytes();
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'`' => {
+                let end = line[i + 1..]
+                    .find('`')
+                    .map_or(line.len(), |end| i + 1 + end + 1);
+                spans.push(Span::new(offset + i..offset + end, STRING));
+                i = end;
+            }
+            b']' if bytes.get(i + 1) == Some(&b'(') => {
+                let end = line[i + 2..]
+                    .find(')')
+                    .map_or(line.len(), |end| i + 2 + end + 1);
+                spans.push(Span::new(offset + i + 1..offset + end, TYPE));
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+fn list_marker_len(line: &str) -> usize {
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return 1;
+    }
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    if digits > 0 && line[digits..].starts_with(". ") {
+        digits + 1
+    } else {
+        0
+    }
+}
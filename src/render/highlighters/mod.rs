@@ -0,0 +1,278 @@
+//! Syntactic highlighting of files which are not analysed by rust-analyzer.
+//!
+//! Highlighters emit the same classes as rust tokens (`keyword`, `string_literal`,
+//! `field` and so on), so non-rust files reuse the theme of the report.
+mod json;
+mod markdown;
+mod shell;
+mod toml;
+mod yaml;
+
+use std::{ops::Range, path::Path};
+
+use crate::render::{syntax_processor::parse_new_lines, HtmlToken};
+
+const BOOL: &str = "bool_literal";
+const COMMENT: &str = "comment";
+const FIELD: &str = "field";
+const KEYWORD: &str = "keyword";
+const LABEL: &str = "label";
+const NUMBER: &str = "numeric_literal";
+const STRING: &str = "string_literal";
+const TYPE: &str = "type";
+const VARIABLE: &str = "variable";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub range: Range<usize>,
+    pub class: &'static str,
+}
+
+impl Span {
+    fn new(range: Range<usize>, class: &'static str) -> Self {
+        Self { range, class }
+    }
+}
+
+pub trait Highlighter {
+    /// Returns highlighted byte ranges of the content.
+    /// Spans must be sorted and must not overlap.
+    fn highlight(&self, content: &str) -> Vec<Span>;
+}
+
+pub fn highlighter_for(path: &Path) -> Option<&'static dyn Highlighter> {
+    let file_name = path.file_name()?.to_str()?;
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    let highlighter: &'static dyn Highlighter = match extension {
+        "toml" => &toml::Toml,
+        "json" => &json::Json,
+        "yml" | "yaml" => &yaml::Yaml,
+        "md" | "markdown" => &markdown::Markdown,
+        "sh" | "bash" | "zsh" => &shell::Shell,
+        _ if matches!(file_name, "justfile" | "Justfile" | "Makefile" | "makefile") => {
+            &shell::Shell
+        }
+        _ => return None,
+    };
+    Some(highlighter)
+}
+
+/// Splits the whole content into tokens, so the file is rendered
+/// the same way as a rust one.
+pub fn highlight_tokens(content: &str, highlighter: &dyn Highlighter) -> Vec<HtmlToken> {
+    let mut tokens = vec![];
+    let mut position = 0;
+    for span in highlighter.highlight(content) {
+        if span.range.is_empty() {
+            continue;
+        }
+        assert!(
+            position <= span.range.start,
+            "highlighter returned overlapping span: {span:?}"
+        );
+        tokens.extend(parse_new_lines(
+            &content[position..span.range.start],
+            position as u32,
+            None,
+        ));
+        tokens.extend(parse_new_lines(
+            &content[span.range.clone()],
+            span.range.start as u32,
+            Some(span.class.to_string()),
+        ));
+        position = span.range.end;
+    }
+    tokens.extend(parse_new_lines(&content[position..], position as u32, None));
+    tokens
+}
+
+/// Iterates over lines and their offsets. Line endings are not included.
+fn lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    content.split('\n').map(move |line| {
+        let start = offset;
+        offset += line.len() + 1;
+        (start, line.strip_suffix('\r').unwrap_or(line))
+    })
+}
+
+/// Returns the end of the quoted string which starts at `start`.
+/// Unterminated string lasts until the end of the line.
+fn quoted_end(line: &str, start: usize, escapes: bool) -> usize {
+    let bytes = line.as_bytes();
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if escapes => i += 2,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the end of the number which starts at `start`, if any.
+fn number_end(line: &str, start: usize) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut i = start;
+    if matches!(bytes.get(i), Some(b'-' | b'+')) {
+        i += 1;
+    }
+    if !bytes.get(i).map_or(false, u8::is_ascii_digit) {
+        return None;
+    }
+    while i < bytes.len()
+        && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'.' | b'_' | b'-' | b':'))
+    {
+        i += 1;
+    }
+    Some(i)
+}
+
+fn word_end(line: &str, start: usize) -> usize {
+    start
+        + line[start..]
+            .bytes()
+            .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-'))
+            .count()
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes(highlighter: &dyn Highlighter, content: &str) -> Vec<(&'static str, String)> {
+        highlighter
+            .highlight(content)
+            .into_iter()
+            .map(|span| (span.class, content[span.range].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight_tokens_cover_content() {
+        let content = "[package]\nname = \"a\" # comment\n\nversion = 1\n";
+        let tokens = highlight_tokens(content, &toml::Toml);
+        let rendered: String = tokens.iter().map(|t| &content[t.range]).collect();
+        assert_eq!(rendered, content);
+        assert_eq!(tokens.iter().filter(|t| t.is_new_line).count(), 4);
+    }
+
+    #[test]
+    fn test_toml() {
+        assert_eq!(
+            classes(
+                &toml::Toml,
+                "[dependencies]\nsyn = { version = \"1.0\", default-features = false }\n"
+            ),
+            vec![
+                (TYPE, "[dependencies]".into()),
+                (FIELD, "syn".into()),
+                (FIELD, "version".into()),
+                (STRING, "\"1.0\"".into()),
+                (FIELD, "default-features".into()),
+                (BOOL, "false".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_toml_nested_arrays_are_not_tables() {
+        assert_eq!(
+            classes(
+                &toml::Toml,
+                "matrix = [\n    [1, 2],\n    [\"a\"],\n]\n[package]\n"
+            ),
+            vec![
+                (FIELD, "matrix".into()),
+                (NUMBER, "1".into()),
+                (NUMBER, "2".into()),
+                (STRING, "\"a\"".into()),
+                (TYPE, "[package]".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            classes(&json::Json, "{\"a\": [1, -2.5e3, true, null, \"x\\\"y\"]}"),
+            vec![
+                (FIELD, "\"a\"".into()),
+                (NUMBER, "1".into()),
+                (NUMBER, "-2.5e3".into()),
+                (BOOL, "true".into()),
+                (KEYWORD, "null".into()),
+                (STRING, "\"x\\\"y\"".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yaml() {
+        assert_eq!(
+            classes(
+                &yaml::Yaml,
+                "# config\n---\nname: \"a # b\" # comment\nitems:\n  - count: 3\n    on: true\n  - *anchor\n"
+            ),
+            vec![
+                (COMMENT, "# config".into()),
+                (KEYWORD, "---".into()),
+                (FIELD, "name".into()),
+                (STRING, "\"a # b\"".into()),
+                (COMMENT, "# comment".into()),
+                (FIELD, "items".into()),
+                (FIELD, "count".into()),
+                (NUMBER, "3".into()),
+                (FIELD, "on".into()),
+                (BOOL, "true".into()),
+                (LABEL, "*anchor".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(
+            classes(
+                &markdown::Markdown,
+                "# Title\n> quote\n- see [docs](a.md) and `code`\n```rust\nlet a = 1;\n```\n"
+            ),
+            vec![
+                (KEYWORD, "# Title".into()),
+                (COMMENT, "> quote".into()),
+                (LABEL, "-".into()),
+                (TYPE, "(a.md)".into()),
+                (STRING, "`code`".into()),
+                (STRING, "```rust\nlet a = 1;\n```".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell() {
+        assert_eq!(
+            classes(
+                &shell::Shell,
+                "if [ -n \"$A\" ]; then echo ${B} 'c#d' # done\nfi\n"
+            ),
+            vec![
+                (KEYWORD, "if".into()),
+                (STRING, "\"$A\"".into()),
+                (KEYWORD, "then".into()),
+                (VARIABLE, "${B}".into()),
+                (STRING, "'c#d'".into()),
+                (COMMENT, "# done".into()),
+                (KEYWORD, "fi".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use super::{
+    is_word_byte, lines, quoted_end, Highlighter, Span, COMMENT, KEYWORD, STRING, VARIABLE,
+};
+
+const KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+    "in", "function", "return", "export", "local", "readonly", "set", "unset", "source", "exit",
+];
+
+pub struct Shell;
+
+impl Highlighter for Shell {
+    fn highlight(&self, content: &str) -> Vec<Span> {
+        let mut spans = vec![];
+        for (offset, line) in lines(content) {
+            let bytes = line.as_bytes();
+            let mut i = 0;
+            while i < bytes.len() {
+                let start = i;
+                let class = match bytes[i] {
+                    b'#' if i == 0 || bytes[i - 1].is_ascii_whitespace() => {
+                        i = bytes.len();
+                        COMMENT
+                    }
+                    b'"' => {
+                        i = quoted_end(line, i, true);
+                        STRING
+                    }
+                    b'\'' => {
+                        i = quoted_end(line, i, false);
+                        STRING
+                    }
+                    b'$' if bytes.get(i + 1) == Some(&b'{') => {
+                        i = line[i..].find('}').map_or(bytes.len(), |end| i + end + 1);
+                        VARIABLE
+                    }
+                    b'$' if bytes.get(i + 1).map_or(false, |b| {
+                        is_word_byte(*b) || matches!(b, b'@' | b'*' | b'#' | b'?')
+                    }) =>
+                    {
+                        i += 2;
+                        i += line[i..].bytes().take_while(|b| is_word_byte(*b)).count();
+                        VARIABLE
+                    }
+                    b if is_word_byte(b) => {
+                        i += line[i..].bytes().take_while(|b| is_word_byte(*b)).count();
+                        let is_word_start = start == 0 || !is_word_byte(bytes[start - 1]);
+                        if is_word_start && KEYWORDS.contains(&&line[start..i]) {
+                            KEYWORD
+                        } else {
+                            continue;
+                        }
+                    }
+                    _ => {
+                        i += 1;
+                        continue;
+                    }
+                };
+                spans.push(Span::new(offset + start..offset + i, class));
+            }
+        }
+        spans
+    }
+}
@@ -0,0 +1,113 @@
+use super::{
+    lines, number_end, quoted_end, word_end, Highlighter, Span, BOOL, COMMENT, FIELD, NUMBER,
+    STRING, TYPE,
+};
+
+pub struct Toml;
+
+impl Highlighter for Toml {
+    fn highlight(&self, content: &str) -> Vec<Span> {
+        let mut spans = vec![];
+        // start and delimiter of the multi-line string which is not closed yet
+        let mut open_string: Option<(usize, &str)> = None;
+        // brackets of the multi-line arrays which are not closed yet
+        let mut array_depth = 0usize;
+        for (offset, line) in lines(content) {
+            let mut i = if let Some((start, delimiter)) = open_string {
+                match line.find(delimiter) {
+                    Some(end) => {
+                        let end = end + delimiter.len();
+                        spans.push(Span::new(start..offset + end, STRING));
+                        open_string = None;
+                        end
+                    }
+                    None => continue,
+                }
+            } else {
+                let indent = line.len() - line.trim_start().len();
+                if array_depth == 0 && line[indent..].starts_with('[') {
+                    let end = line[indent..]
+                        .find(']')
+                        .map_or(line.len(), |end| indent + end + 1);
+                    let end = if line[end..].starts_with(']') {
+                        end + 1
+                    } else {
+                        end
+                    };
+                    spans.push(Span::new(offset + indent..offset + end, TYPE));
+                    end
+                } else {
+                    indent
+                }
+            };
+
+            let bytes = line.as_bytes();
+            while i < bytes.len() {
+                let start = i;
+                let class = match bytes[i] {
+                    b'#' => {
+                        i = bytes.len();
+                        COMMENT
+                    }
+                    b'"' | b'\'' => {
+                        let delimiter = if bytes[i] == b'"' { "\"\"\"" } else { "'''" };
+                        if line[i..].starts_with(delimiter) {
+                            match line[i + 3..].find(delimiter) {
+                                Some(end) => i += 3 + end + 3,
+                                None => {
+                                    open_string = Some((offset + i, delimiter));
+                                    break;
+                                }
+                            }
+                        } else {
+                            i = quoted_end(line, i, bytes[i] == b'"');
+                        }
+                        if is_key(&line[i..]) {
+                            FIELD
+                        } else {
+                            STRING
+                        }
+                    }
+                    b'0'..=b'9' | b'+' | b'-' if number_end(line, i).is_some() => {
+                        i = number_end(line, i).unwrap_or(i + 1);
+                        NUMBER
+                    }
+                    b if b.is_ascii_alphabetic() || b == b'_' => {
+                        i = word_end(line, i);
+                        match &line[start..i] {
+                            _ if is_key(&line[i..]) => FIELD,
+                            "true" | "false" => BOOL,
+                            "inf" | "nan" => NUMBER,
+                            _ => continue,
+                        }
+                    }
+                    b'[' => {
+                        array_depth += 1;
+                        i += 1;
+                        continue;
+                    }
+                    b']' => {
+                        array_depth = array_depth.saturating_sub(1);
+                        i += 1;
+                        continue;
+                    }
+                    _ => {
+                        i += 1;
+                        continue;
+                    }
+                };
+                spans.push(Span::new(offset + start..offset + i, class));
+            }
+        }
+        if let Some((start, _)) = open_string {
+            spans.push(Span::new(start..content.len(), STRING));
+        }
+        spans
+    }
+}
+
+/// Keys are followed by `=` or by `.` in case of dotted keys.
+fn is_key(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    rest.starts_with('=') || rest.starts_with('.')
+}
@@ -0,0 +1,91 @@
+use super::{
+    lines, number_end, quoted_end, Highlighter, Span, BOOL, COMMENT, FIELD, KEYWORD, LABEL, NUMBER,
+    STRING,
+};
+
+pub struct Yaml;
+
+impl Highlighter for Yaml {
+    fn highlight(&self, content: &str) -> Vec<Span> {
+        let mut spans = vec![];
+        for (offset, line) in lines(content) {
+            let mut i = line.len() - line.trim_start().len();
+            if line[i..].starts_with('#') {
+                spans.push(Span::new(offset + i..offset + line.len(), COMMENT));
+                continue;
+            }
+            if matches!(line.trim_end(), "---" | "...") {
+                spans.push(Span::new(offset..offset + line.trim_end().len(), KEYWORD));
+                continue;
+            }
+            while line[i..].starts_with("- ") {
+                i += 2;
+            }
+
+            // key of a mapping
+            let key_end = if line[i..].starts_with(['"', '\'']) {
+                let end = quoted_end(line, i, line[i..].starts_with('"'));
+                line[end..].starts_with(':').then_some(end)
+            } else {
+                find_key_separator(line, i)
+            };
+            if let Some(key_end) = key_end {
+                let key = line[i..key_end].trim_end();
+                if !key.is_empty() {
+                    spans.push(Span::new(offset + i..offset + i + key.len(), FIELD));
+                }
+                i = key_end + 1;
+            }
+
+            // the rest of the line is a scalar value
+            let value_start = i + line[i..].len() - line[i..].trim_start().len();
+            // `#` inside of a quoted value does not start a comment
+            let comment_search_start = match line[value_start..].as_bytes().first() {
+                Some(quote @ (b'"' | b'\'')) => quoted_end(line, value_start, *quote == b'"'),
+                _ => value_start,
+            };
+            let value_end = find_comment(line, comment_search_start).unwrap_or(line.len());
+            let value = line[value_start..value_end].trim_end();
+            let value_end = value_start + value.len();
+            let class = match value.as_bytes().first() {
+                None => None,
+                Some(b'"' | b'\'') => Some(STRING),
+                Some(b'&' | b'*') => Some(LABEL),
+                Some(_) if number_end(value, 0) == Some(value.len()) => Some(NUMBER),
+                Some(_) => match value {
+                    "true" | "false" | "yes" | "no" | "True" | "False" => Some(BOOL),
+                    "null" | "~" => Some(KEYWORD),
+                    _ => None,
+                },
+            };
+            if let Some(class) = class {
+                spans.push(Span::new(offset + value_start..offset + value_end, class));
+            }
+            if value_end < line.len() {
+                if let Some(comment) = find_comment(line, value_end) {
+                    spans.push(Span::new(offset + comment..offset + line.len(), COMMENT));
+                }
+            }
+        }
+        spans
+    }
+}
+
+/// Finds `:` which separates a plain key from its value.
+fn find_key_separator(line: &str, start: usize) -> Option<usize> {
+    if line[start..].starts_with(['{', '[', '&', '*', '|', '>', '#']) {
+        return None;
+    }
+    let bytes = line.as_bytes();
+    (start..bytes.len())
+        .find(|&i| bytes[i] == b':' && bytes.get(i + 1).map_or(true, |b| b.is_ascii_whitespace()))
+}
+
+/// Comments start with `#` preceded by a whitespace.
+fn find_comment(line: &str, start: usize) -> Option<usize> {
+    let bytes = line.as_bytes();
+    if bytes.get(start) == Some(&b'#') {
+        return Some(start);
+    }
+    (start.max(1)..bytes.len()).find(|&i| bytes[i] == b'#' && bytes[i - 1].is_ascii_whitespace())
+}
@@ -1,8 +1,10 @@
 mod compressor;
 mod generators;
+mod highlighters;
 pub mod static_files;
 mod syntax_processor;
 
 pub use compressor::*;
 pub use generators::*;
+pub use static_files::templates::override_templates;
 pub use syntax_processor::*;
@@ -7,6 +7,12 @@ lazy_static::lazy_static! {
             "css/svgs.css",
             "css/fold.css",
             "css/jump.css",
+            "css/expansion.css",
+            "css/markdown.css",
+            "css/reports.css",
+            "css/themes/dark.css",
+            "css/themes/light.css",
+            "css/themes/high-contrast.css",
         ].map(|name| {
             std::fs::read_to_string(name).unwrap_or_else(|_| panic!("cannot read file {name}"))
         }).join("\n")
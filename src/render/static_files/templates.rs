@@ -1,8 +1,9 @@
 use lazy_static::lazy_static;
-use tera::Tera;
+use std::{fs, path::Path, sync::RwLock};
+use tera::{Context, Tera};
 
 lazy_static! {
-    pub static ref TEMPLATES: Tera = {
+    static ref TEMPLATES: RwLock<Tera> = {
         let tera = match Tera::new("src/templates/*.html") {
             Ok(t) => t,
             Err(e) => {
@@ -10,6 +11,36 @@ lazy_static! {
                 ::std::process::exit(1);
             }
         };
-        tera
+        RwLock::new(tera)
     };
 }
+
+pub fn render(name: &str, context: &Context) -> Result<String, tera::Error> {
+    TEMPLATES
+        .read()
+        .expect("templates are not poisoned")
+        .render(name, context)
+}
+
+/// Replaces the built-in templates by the `*.html` files of `dir` with the same names,
+/// the other files can be used by `include`, `import` and `extends`
+pub fn override_templates(dir: &Path) -> Result<(), anyhow::Error> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .extension()
+            .map_or(false, |extension| extension == "html")
+        {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string());
+            files.push((path, name));
+        }
+    }
+    TEMPLATES
+        .write()
+        .expect("templates are not poisoned")
+        .add_template_files(files)?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use syntax::{ast, AstNode, NodeOrToken, SourceFile, SyntaxKind as SK, SyntaxToken, T};
+
+/// Highlights the text of a macro expansion.
+///
+/// Expanded code is not a part of any file known to rust-analyzer, so only
+/// syntactic highlighting is possible here. Class names are the same as
+/// the ones produced for regular tokens, so the theme is reused.
+pub fn highlight_expansion(text: &str) -> String {
+    let file = SourceFile::parse(text).syntax_node();
+    file.descendants_with_tokens()
+        .filter_map(NodeOrToken::into_token)
+        .map(|token| {
+            let content = html_escape::encode_text(token.text());
+            match expansion_class(&token) {
+                Some(class) => format!("<span class=\"{class}\">{content}</span>"),
+                None => content.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn expansion_class(token: &SyntaxToken) -> Option<&'static str> {
+    let kind = token.kind();
+    let class = match kind {
+        T![true] | T![false] => "bool_literal",
+        SK::COMMENT => "comment",
+        SK::STRING | SK::BYTE_STRING | SK::C_STRING | SK::CHAR | SK::BYTE => "string_literal",
+        SK::INT_NUMBER | SK::FLOAT_NUMBER => "numeric_literal",
+        SK::LIFETIME_IDENT => "lifetime",
+        SK::IDENT => ident_class(token)?,
+        _ if kind.is_keyword() => "keyword",
+        _ => return None,
+    };
+    Some(class)
+}
+
+fn ident_class(token: &SyntaxToken) -> Option<&'static str> {
+    let parent = token.parent()?;
+    if let Some(name) = ast::Name::cast(parent.clone()) {
+        let item = name.syntax().parent()?;
+        return match item.kind() {
+            SK::FN => Some("function"),
+            SK::STRUCT | SK::ENUM | SK::UNION | SK::TYPE_ALIAS => Some("type"),
+            SK::TRAIT => Some("trait"),
+            SK::MODULE => Some("module"),
+            SK::RECORD_FIELD => Some("field"),
+            SK::VARIANT => Some("enum_variant"),
+            _ => Some("variable"),
+        };
+    }
+    ast::NameRef::cast(parent)?;
+    let is_macro_call = token
+        .next_token()
+        .map_or(false, |next| next.kind() == T![!]);
+    if is_macro_call {
+        Some("macro")
+    } else if token.text().starts_with(char::is_uppercase) {
+        Some("type")
+    } else {
+        None
+    }
+}
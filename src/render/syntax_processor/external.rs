@@ -0,0 +1,215 @@
+use ide_db::{
+    base_db::{CrateOrigin, SourceDatabase},
+    RootDatabase,
+};
+use std::path::{Path, PathBuf};
+use vfs::Vfs;
+
+use crate::{
+    parser::ReportRoot,
+    render::{JumpDestination, JumpLocation},
+    Settings,
+};
+
+/// Crates of the sysroot rendered in the report with `--scan-whole`
+const SYSROOT_CRATES: &[&str] = &["std", "core", "alloc"];
+
+const LANG_URL_TEMPLATE: &str = "https://doc.rust-lang.org/nightly/src/{crate}/{path}.html#{line}";
+
+#[derive(Debug)]
+struct ExternalCrate {
+    name: String,
+    version: Option<String>,
+    is_lang: bool,
+    package_dir: PathBuf,
+    root_file: PathBuf,
+    /// Sources of the crate are rendered in the report
+    included: bool,
+}
+
+impl ExternalCrate {
+    fn root_name(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{}-{version}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Crates of the workspace which live outside of the project directory:
+/// dependencies and, when the sysroot is loaded, `std`, `core` and `alloc`.
+#[derive(Debug)]
+pub struct ExternalCrates {
+    crates: Vec<ExternalCrate>,
+    url_template: String,
+}
+
+impl ExternalCrates {
+    pub fn new(db: &RootDatabase, vfs: &Vfs, settings: &Settings) -> Self {
+        let crate_graph = db.crate_graph();
+        let mut crates: Vec<_> = crate_graph
+            .iter()
+            .filter_map(|crate_id| {
+                let data = &crate_graph[crate_id];
+                let root = vfs.file_path(data.root_file_id);
+                let root_file = root.as_path()?.as_ref().to_path_buf();
+                let package_dir = root_file
+                    .ancestors()
+                    .find(|dir| dir.join("Cargo.toml").is_file())?
+                    .to_path_buf();
+                let name = data.display_name.as_ref()?.canonical_name().to_string();
+                let is_lang = matches!(data.origin, CrateOrigin::Lang(_));
+                Some(ExternalCrate {
+                    included: settings.include_crates.contains(&name)
+                        || (settings.scan_whole
+                            && is_lang
+                            && SYSROOT_CRATES.contains(&name.as_str())),
+                    name,
+                    version: data.version.clone(),
+                    is_lang,
+                    package_dir,
+                    root_file,
+                })
+            })
+            .filter(|krate| !krate.package_dir.starts_with(&settings.dir))
+            .collect();
+        // nested packages must be matched before the outer ones
+        crates.sort_by_key(|krate| std::cmp::Reverse(krate.package_dir.components().count()));
+        for name in settings.include_crates.iter() {
+            if !crates.iter().any(|krate| &krate.name == name) {
+                println!("WARN: crate '{name}' is not found in the workspace");
+            }
+        }
+        Self {
+            crates,
+            url_template: settings.external_url_template.clone(),
+        }
+    }
+
+    /// Directories rendered in the report: the project itself
+    /// followed by packages of the included crates.
+    pub fn report_roots(&self, settings: &Settings) -> Vec<ReportRoot> {
+        let mut roots = vec![ReportRoot {
+            name: settings.project_name.clone(),
+            path: settings.dir.clone(),
+            external: false,
+        }];
+        for krate in self.crates.iter().filter(|krate| krate.included) {
+            // several crates (e.g. lib and bin targets) may share one package
+            if roots.iter().all(|root| root.path != krate.package_dir) {
+                roots.push(ReportRoot {
+                    name: krate.root_name(),
+                    path: krate.package_dir.clone(),
+                    external: true,
+                });
+            }
+        }
+        roots
+    }
+
+    /// Builds destination for a file of the external crate. Included crates and
+    /// the sysroot with `--scan-whole` are rendered in the report, other ones are linked
+    /// to docs.rs (or the configured registry) and doc.rust-lang.org for the standard library.
+    pub fn jump(&self, path: &Path, location: JumpLocation) -> Option<JumpDestination> {
+        let krate = self
+            .crates
+            .iter()
+            .find(|krate| path.starts_with(&krate.package_dir))?;
+        let package_path = path.strip_prefix(&krate.package_dir).ok()?;
+        let file = format!("{}/{}", krate.root_name(), package_path.to_string_lossy());
+        if krate.included {
+            return Some(JumpDestination::new(file, location));
+        }
+        let (template, path) = if krate.is_lang {
+            (LANG_URL_TEMPLATE, package_path.strip_prefix("src").ok()?)
+        } else {
+            (self.url_template.as_str(), package_path)
+        };
+        let version = krate.version.as_deref().unwrap_or("latest");
+        let url = fill_url_template(
+            template,
+            &krate.name,
+            version,
+            &path.to_string_lossy(),
+            &location,
+        );
+        Some(JumpDestination::new(file, location).with_url(url))
+    }
+
+    /// Builds destination for a dependency of `Cargo.toml`: the root file of the crate
+    /// when it is in the workspace, otherwise the URL of the template with the version
+    /// required by the manifest.
+    pub fn dependency_jump(&self, package: &str, version: Option<&str>) -> JumpDestination {
+        let location = JumpLocation { line: 1 };
+        let jump = self
+            .crates
+            .iter()
+            .filter(|krate| !krate.is_lang && krate.name == package)
+            .find_map(|krate| self.jump(&krate.root_file, location.clone()));
+        if let Some(jump) = jump {
+            return jump;
+        }
+        // `1.0` and `=1.0.3` are versions of the template, ranges are not
+        let version = version
+            .map(|version| version.trim_start_matches(['^', '=', '~']).trim())
+            .filter(|version| {
+                !version.is_empty()
+                    && version
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'))
+            })
+            .unwrap_or("latest");
+        let path = "src/lib.rs";
+        let url = fill_url_template(&self.url_template, package, version, path, &location);
+        JumpDestination::new(format!("{package}-{version}/{path}"), location).with_url(url)
+    }
+}
+
+fn fill_url_template(
+    template: &str,
+    name: &str,
+    version: &str,
+    path: &str,
+    location: &JumpLocation,
+) -> String {
+    template
+        .replace("{crate}", name)
+        .replace("{version}", version)
+        .replace("{path}", path)
+        .replace("{line}", &location.line.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependency_jump() {
+        let crates = ExternalCrates {
+            crates: vec![ExternalCrate {
+                name: "ide".to_string(),
+                version: Some("0.0.0".to_string()),
+                is_lang: false,
+                package_dir: PathBuf::from("/ra/crates/ide"),
+                root_file: PathBuf::from("/ra/crates/ide/src/lib.rs"),
+                included: false,
+            }],
+            url_template: "https://src.example/{crate}/{version}/{path}#{line}".to_string(),
+        };
+        let url = |package: &str, version: Option<&str>| {
+            crates.dependency_jump(package, version).url.unwrap()
+        };
+        assert_eq!(
+            url("ide", None),
+            "https://src.example/ide/0.0.0/src/lib.rs#1"
+        );
+        assert_eq!(
+            url("serde", Some("^1.0")),
+            "https://src.example/serde/1.0/src/lib.rs#1"
+        );
+        assert_eq!(
+            url("syn", Some(">=1, <3")),
+            "https://src.example/syn/latest/src/lib.rs#1"
+        );
+    }
+}
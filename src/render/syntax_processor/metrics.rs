@@ -0,0 +1,188 @@
+use ide::LineIndex;
+use serde::Serialize;
+use syntax::{
+    ast::{self, HasName},
+    match_ast, AstNode, SyntaxKind as SK, SyntaxNode,
+    WalkEvent::{Enter, Leave},
+};
+
+/// Functions reaching any of the limits are hotspots
+const MAX_LINES: u32 = 60;
+const MAX_COMPLEXITY: u32 = 10;
+const MAX_NESTING: u32 = 4;
+const MAX_PARAMS: u32 = 6;
+
+/// Size and complexity of a function, methods are named after their impl or trait
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionMetrics {
+    pub name: String,
+    pub line: u32,
+    pub lines: u32,
+    /// Cyclomatic complexity: one plus the branches of the body
+    pub complexity: u32,
+    /// Deepest nesting of conditions, loops and closures
+    pub nesting: u32,
+    pub params: u32,
+    pub hotspot: bool,
+}
+
+impl FunctionMetrics {
+    fn new(name: String, line: u32, lines: u32, body: &SyntaxNode, params: u32) -> Self {
+        let (complexity, nesting) = body_metrics(body);
+        Self {
+            hotspot: lines >= MAX_LINES
+                || complexity >= MAX_COMPLEXITY
+                || nesting >= MAX_NESTING
+                || params >= MAX_PARAMS,
+            name,
+            line,
+            lines,
+            complexity,
+            nesting,
+            params,
+        }
+    }
+}
+
+/// Metrics of the functions with bodies, nested functions are measured separately
+pub fn function_metrics(root: &SyntaxNode, finder: &LineIndex) -> Vec<FunctionMetrics> {
+    root.descendants()
+        .filter_map(ast::Fn::cast)
+        .filter_map(|function| {
+            let body = function.body()?;
+            let name = function.name()?;
+            let start = finder.line_col(name.syntax().text_range().start()).line;
+            let end = finder.line_col(function.syntax().text_range().end()).line;
+            let name = match owner_name(function.syntax()) {
+                Some(owner) => format!("{owner}::{name}"),
+                None => name.to_string(),
+            };
+            let params = function.param_list().map_or(0, |params| {
+                params.params().count() as u32 + u32::from(params.self_param().is_some())
+            });
+            Some(FunctionMetrics::new(
+                name,
+                start + 1,
+                end - start + 1,
+                body.syntax(),
+                params,
+            ))
+        })
+        .collect()
+}
+
+fn owner_name(function: &SyntaxNode) -> Option<String> {
+    // fn -> assoc item list -> impl or trait
+    let owner = function.parent()?.parent()?;
+    let name = match_ast! {
+        match owner {
+            ast::Impl(it) => it.self_ty()?.syntax().to_string(),
+            ast::Trait(it) => it.name()?.to_string(),
+            _ => return None,
+        }
+    };
+    Some(name.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+fn body_metrics(body: &SyntaxNode) -> (u32, u32) {
+    let mut complexity = 1;
+    let mut nesting = 0;
+    let mut depth = 0;
+    let mut preorder = body.preorder();
+    while let Some(event) = preorder.next() {
+        match event {
+            Enter(node) if node.kind() == SK::FN => preorder.skip_subtree(),
+            Enter(node) => {
+                complexity += branches(&node);
+                if is_nested(&node) {
+                    depth += 1;
+                    nesting = nesting.max(depth);
+                }
+            }
+            Leave(node) => {
+                if is_nested(&node) {
+                    depth -= 1;
+                }
+            }
+        }
+    }
+    (complexity, nesting)
+}
+
+fn branches(node: &SyntaxNode) -> u32 {
+    match node.kind() {
+        SK::IF_EXPR | SK::WHILE_EXPR | SK::FOR_EXPR | SK::TRY_EXPR => 1,
+        // the first arm is the path which is always there
+        SK::MATCH_EXPR => ast::MatchExpr::cast(node.clone())
+            .and_then(|it| it.match_arm_list())
+            .map_or(0, |arms| arms.arms().count().saturating_sub(1) as u32),
+        SK::BIN_EXPR => {
+            let op = ast::BinExpr::cast(node.clone()).and_then(|it| it.op_kind());
+            u32::from(matches!(op, Some(ast::BinaryOp::LogicOp(_))))
+        }
+        _ => 0,
+    }
+}
+
+fn is_nested(node: &SyntaxNode) -> bool {
+    match node.kind() {
+        // `else if` continues the outer condition
+        SK::IF_EXPR => node
+            .parent()
+            .map_or(true, |parent| parent.kind() != SK::IF_EXPR),
+        SK::WHILE_EXPR | SK::FOR_EXPR | SK::LOOP_EXPR | SK::MATCH_EXPR | SK::CLOSURE_EXPR => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_metrics() {
+        let text = r#"
+struct S;
+impl S {
+    /// Docs are not counted
+    fn check(&self, a: u32, b: u32) -> Option<u32> {
+        if a > 0 && b > 0 {
+            for i in 0..a {
+                match i {
+                    0 => {}
+                    1 => {}
+                    _ => return None,
+                }
+            }
+        } else if a == 0 {
+            fn helper() {}
+        }
+        let c = Some(a)?;
+        Some(c)
+    }
+}
+trait T {
+    fn declared(&self);
+}
+"#;
+        let file = ast::SourceFile::parse(text).tree();
+        let metrics = function_metrics(file.syntax(), &LineIndex::new(text));
+        let metrics: Vec<_> = metrics
+            .iter()
+            .map(|m| {
+                (
+                    m.name.as_str(),
+                    m.line,
+                    m.lines,
+                    m.complexity,
+                    m.nesting,
+                    m.params,
+                )
+            })
+            .collect();
+        assert_eq!(
+            metrics,
+            vec![("S::check", 5, 15, 8, 3, 3), ("helper", 15, 1, 1, 0, 0)]
+        );
+    }
+}
@@ -1,5 +1,19 @@
+mod expansion;
+mod external;
 mod folding;
+mod metrics;
+mod modules;
+mod outline;
 mod processor;
+mod runnables;
+mod scopes;
 
+pub use external::ExternalCrates;
 pub use folding::{FoldingRange, FoldingRanges};
+pub use metrics::FunctionMetrics;
+pub use modules::{ModuleItem, ModuleKind, ModuleNode};
+pub use outline::OutlineNode;
+pub(crate) use processor::parse_new_lines;
 pub use processor::SyntaxProcessor;
+pub use runnables::{run_commands, RunCommand, RunCommands, RunKind, Runnable};
+pub use scopes::{Scope, Scopes};
@@ -0,0 +1,177 @@
+use hir::{Crate, HasSource, HasVisibility, InFile, Module, ModuleDef, ModuleSource, Visibility};
+use ide::{FileId, FileRange};
+use ide_db::RootDatabase;
+use syntax::{ast::HasAttrs, AstNode, SyntaxNode};
+
+use crate::render::JumpDestination;
+
+/// Module of a crate as rust-analyzer sees it, with the items declared in it
+#[derive(Debug, Clone)]
+pub struct ModuleNode {
+    pub name: String,
+    pub kind: ModuleKind,
+    pub jump: Option<JumpDestination>,
+    pub items: Vec<ModuleItem>,
+    pub children: Vec<ModuleNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleKind {
+    CrateRoot,
+    File,
+    /// File set with the `#[path]` attribute
+    Path,
+    Inline,
+    Block,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleItem {
+    pub name: String,
+    pub kind: &'static str,
+    pub visibility: &'static str,
+    pub jump: Option<JumpDestination>,
+}
+
+/// Builds module trees of the crates which root files are rendered in the report,
+/// `locate` converts ranges of the sources into the destinations of the report.
+pub fn module_trees(
+    db: &RootDatabase,
+    is_rendered: impl Fn(FileId) -> bool,
+    locate: impl Fn(FileRange) -> Option<JumpDestination>,
+) -> Vec<ModuleNode> {
+    let mut crates: Vec<_> = Crate::all(db)
+        .into_iter()
+        .filter(|krate| is_rendered(krate.root_file(db)))
+        .collect();
+    crates.sort_by_key(|krate| crate_name(db, *krate));
+    crates
+        .into_iter()
+        .map(|krate| module_node(db, krate.root_module(db), &locate))
+        .collect()
+}
+
+fn crate_name(db: &RootDatabase, krate: Crate) -> String {
+    krate
+        .display_name(db)
+        .map_or_else(|| "<crate>".to_string(), |name| name.to_string())
+}
+
+fn module_node(
+    db: &RootDatabase,
+    module: Module,
+    locate: &impl Fn(FileRange) -> Option<JumpDestination>,
+) -> ModuleNode {
+    let source = module.definition_source(db);
+    let file_id = source.file_id.original_file(db);
+    let (kind, range) = match &source.value {
+        ModuleSource::SourceFile(file) => {
+            let has_path_attr = module.declaration_source(db).map_or(false, |declaration| {
+                declaration.value.attrs().any(|attr| {
+                    attr.path()
+                        .map_or(false, |path| path.syntax().text() == "path")
+                })
+            });
+            let kind = if module.parent(db).is_none() {
+                ModuleKind::CrateRoot
+            } else if has_path_attr {
+                ModuleKind::Path
+            } else {
+                ModuleKind::File
+            };
+            (kind, file.syntax().text_range())
+        }
+        ModuleSource::Module(module) => (ModuleKind::Inline, module.syntax().text_range()),
+        ModuleSource::BlockExpr(block) => (ModuleKind::Block, block.syntax().text_range()),
+    };
+    let name = match kind {
+        ModuleKind::CrateRoot => crate_name(db, module.krate()),
+        _ => module
+            .name(db)
+            .map_or_else(|| "<block>".to_string(), |name| name.to_string()),
+    };
+    let items = module
+        .declarations(db)
+        .into_iter()
+        .filter(|def| !matches!(def, ModuleDef::Module(_)))
+        .filter_map(|def| {
+            Some(ModuleItem {
+                name: def.name(db)?.to_string(),
+                kind: item_kind(def),
+                visibility: visibility(db, module, def),
+                jump: item_range(db, def).and_then(locate),
+            })
+        })
+        .collect();
+    let mut children = module.children(db).collect::<Vec<_>>();
+    children.sort_by_key(|child| child.name(db).map(|name| name.to_string()));
+    ModuleNode {
+        name,
+        kind,
+        jump: locate(FileRange { file_id, range }),
+        items,
+        children: children
+            .into_iter()
+            .map(|child| module_node(db, child, locate))
+            .collect(),
+    }
+}
+
+fn item_kind(def: ModuleDef) -> &'static str {
+    match def {
+        ModuleDef::Module(_) => "module",
+        ModuleDef::Function(_) => "function",
+        ModuleDef::Adt(hir::Adt::Struct(_)) => "struct",
+        ModuleDef::Adt(hir::Adt::Enum(_)) => "enum",
+        ModuleDef::Adt(hir::Adt::Union(_)) => "union",
+        ModuleDef::Variant(_) => "enum_variant",
+        ModuleDef::Const(_) => "constant",
+        ModuleDef::Static(_) => "static",
+        ModuleDef::Trait(_) => "trait",
+        ModuleDef::TypeAlias(_) => "type_alias",
+        ModuleDef::BuiltinType(_) => "builtin_type",
+        ModuleDef::Macro(_) => "macro",
+    }
+}
+
+fn visibility(db: &RootDatabase, module: Module, def: ModuleDef) -> &'static str {
+    let visibility = match def {
+        ModuleDef::Function(it) => it.visibility(db),
+        ModuleDef::Adt(it) => it.visibility(db),
+        ModuleDef::Const(it) => it.visibility(db),
+        ModuleDef::Static(it) => it.visibility(db),
+        ModuleDef::Trait(it) => it.visibility(db),
+        ModuleDef::TypeAlias(it) => it.visibility(db),
+        ModuleDef::Macro(it) => it.visibility(db),
+        _ => return "",
+    };
+    match visibility {
+        Visibility::Public => "pub",
+        Visibility::Module(id) if Module::from(id) == module => "",
+        Visibility::Module(id) if Module::from(id) == module.krate().root_module(db) => {
+            "pub(crate)"
+        }
+        Visibility::Module(_) => "pub(in ..)",
+    }
+}
+
+fn item_range(db: &RootDatabase, def: ModuleDef) -> Option<FileRange> {
+    let source = match def {
+        ModuleDef::Function(it) => syntax(it.source(db)),
+        ModuleDef::Adt(hir::Adt::Struct(it)) => syntax(it.source(db)),
+        ModuleDef::Adt(hir::Adt::Enum(it)) => syntax(it.source(db)),
+        ModuleDef::Adt(hir::Adt::Union(it)) => syntax(it.source(db)),
+        ModuleDef::Variant(it) => syntax(it.source(db)),
+        ModuleDef::Const(it) => syntax(it.source(db)),
+        ModuleDef::Static(it) => syntax(it.source(db)),
+        ModuleDef::Trait(it) => syntax(it.source(db)),
+        ModuleDef::TypeAlias(it) => syntax(it.source(db)),
+        ModuleDef::Module(_) | ModuleDef::BuiltinType(_) | ModuleDef::Macro(_) => None,
+    }?;
+    // items generated by macros are shown at the macro call
+    Some(source.as_ref().original_file_range(db))
+}
+
+fn syntax<N: AstNode>(source: Option<InFile<N>>) -> Option<InFile<SyntaxNode>> {
+    source.map(|source| source.map(|node| node.syntax().clone()))
+}
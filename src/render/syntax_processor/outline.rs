@@ -0,0 +1,53 @@
+use ide::{LineIndex, StructureNode, StructureNodeKind};
+use serde::Serialize;
+
+/// Symbol of the file outline: structs, impls, functions, etc.
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlineNode {
+    pub label: String,
+    pub kind: String,
+    pub detail: Option<String>,
+    pub line: u32,
+    pub children: Vec<OutlineNode>,
+}
+
+impl OutlineNode {
+    /// Builds the tree of symbols from the flat list where parents precede their children
+    pub fn from_structure(nodes: Vec<StructureNode>, finder: &LineIndex) -> Vec<OutlineNode> {
+        let mut children: Vec<Vec<usize>> = vec![vec![]; nodes.len()];
+        let mut roots = vec![];
+        for (index, node) in nodes.iter().enumerate() {
+            match node.parent {
+                Some(parent) => children[parent].push(index),
+                None => roots.push(index),
+            }
+        }
+        roots
+            .into_iter()
+            .map(|index| Self::build(index, &nodes, &children, finder))
+            .collect()
+    }
+
+    fn build(
+        index: usize,
+        nodes: &[StructureNode],
+        children: &[Vec<usize>],
+        finder: &LineIndex,
+    ) -> OutlineNode {
+        let node = &nodes[index];
+        let kind = match node.kind {
+            StructureNodeKind::SymbolKind(kind) => format!("{kind:?}").to_lowercase(),
+            StructureNodeKind::Region => "region".to_string(),
+        };
+        OutlineNode {
+            label: node.label.clone(),
+            kind,
+            detail: node.detail.clone(),
+            line: finder.line_col(node.navigation_range.start()).line + 1,
+            children: children[index]
+                .iter()
+                .map(|child| Self::build(*child, nodes, children, finder))
+                .collect(),
+        }
+    }
+}
@@ -1,38 +1,72 @@
-use hir::Semantics;
+use hir::{HasVisibility, Module, Semantics, Visibility};
 use ide::{
-    Analysis, AnalysisHost, ClosureReturnTypeHints, FileId, FilePosition, FileRange, Highlight,
-    HighlightConfig, HoverConfig, InlayHintsConfig, LineIndex, NavigationTarget,
-    ReferenceSearchResult, SearchScope, TextRange,
+    Analysis, AnalysisHost, CallItem, ClosureReturnTypeHints, FileId, FilePosition, FileRange,
+    Highlight, HighlightConfig, HlMod, HlRange, HlTag, HoverConfig, InlayHintsConfig, LineIndex,
+    NavigationTarget, ReferenceCategory, ReferenceSearchResult, RunnableKind, SearchScope,
+    TextRange,
 };
+use ide_db::{base_db::SourceDatabase, RootDatabase};
 use std::{collections::HashMap, path::Path, sync::Arc};
 use syntax::{
-    AstNode, AstToken, NodeOrToken, SyntaxKind as SK, SyntaxNode, SyntaxToken,
+    ast::{self, HasVisibility as _},
+    match_ast, AstNode, AstToken, NodeOrToken, SyntaxKind as SK, SyntaxNode, SyntaxToken,
     WalkEvent::{Enter, Leave},
 };
 use vfs::{Vfs, VfsPath};
 
 use crate::{
-    render::{HtmlToken, JumpDestination, JumpLocation, Navigation},
-    Settings,
+    metadata::Package,
+    render::{
+        CallHierarchy, CallJump, HtmlToken, ItemKind, JumpDestination, JumpLocation,
+        MacroExpansion, Navigation, TaskKind, UnsafeKind, UnusedItem,
+    },
+    HighlightOptions, Settings,
 };
 
-use super::{folding::FoldingRanges, FoldingRange};
+use super::{
+    expansion::highlight_expansion,
+    external::ExternalCrates,
+    folding::FoldingRanges,
+    metrics::{function_metrics, FunctionMetrics},
+    modules::{module_trees, ModuleNode},
+    outline::OutlineNode,
+    runnables::{
+        bin_name, cargo_target, doctest_starts, is_library_root, CargoTarget, RunKind, Runnable,
+    },
+    scopes::Scopes,
+    FoldingRange,
+};
 
 pub struct SyntaxProcessor {
     host: AnalysisHost,
     vfs: Vfs,
     all_files: Vec<FileId>,
+    external_crates: ExternalCrates,
+    /// Packages of the workspace from `cargo metadata`
+    packages: Vec<Package>,
 }
 
 impl SyntaxProcessor {
-    pub fn new(host: AnalysisHost, vfs: Vfs, all_files: Vec<FileId>) -> Self {
+    pub fn new(
+        host: AnalysisHost,
+        vfs: Vfs,
+        all_files: Vec<FileId>,
+        external_crates: ExternalCrates,
+        packages: Vec<Package>,
+    ) -> Self {
         Self {
             host,
             vfs,
             all_files,
+            external_crates,
+            packages,
         }
     }
 
+    pub fn external_crates(&self) -> &ExternalCrates {
+        &self.external_crates
+    }
+
     pub fn get_folding_ranges(&self, file_id: FileId) -> FoldingRanges {
         let finder = self.line_finder(file_id);
         self.host
@@ -45,6 +79,116 @@ impl SyntaxProcessor {
             .collect()
     }
 
+    pub fn get_runnables(&self, file_id: FileId) -> Vec<Runnable> {
+        let analysis = self.host.analysis();
+        let finder = self.line_finder(file_id);
+        let text = analysis
+            .file_text(file_id)
+            .expect("RA task cannot be cancelled");
+        let target = self.cargo_target(file_id);
+        let mut result = vec![];
+        let runnables = analysis
+            .runnables(file_id)
+            .expect("RA task cannot be cancelled");
+        for runnable in runnables {
+            let line = finder
+                .line_col(runnable.nav.focus_or_full_range().start())
+                .line
+                + 1;
+            let (kind, name, ignored) = match &runnable.kind {
+                RunnableKind::Test { test_id, attr } => {
+                    (RunKind::Test, test_id.to_string(), attr.ignore)
+                }
+                RunnableKind::TestMod { path } => (RunKind::TestMod, path.clone(), false),
+                RunnableKind::Bench { test_id } => (RunKind::Bench, test_id.to_string(), false),
+                RunnableKind::Bin => {
+                    let name = match &target {
+                        Some(target) if !target.name.is_empty() => Some(target.name.clone()),
+                        _ => {
+                            let path = self.vfs.file_path(file_id);
+                            path.as_path().and_then(|path| bin_name(path.as_ref()))
+                        }
+                    };
+                    (RunKind::Bin, name.unwrap_or_default(), false)
+                }
+                // doctests are marked at the code blocks
+                RunnableKind::DocTest { test_id } => {
+                    for start in doctest_starts(&text, runnable.nav.full_range) {
+                        let line = finder.line_col(start).line + 1;
+                        let name = test_id.to_string();
+                        let doctest =
+                            Runnable::new(RunKind::DocTest, name, target.as_ref(), line, false);
+                        result.push(doctest);
+                    }
+                    continue;
+                }
+            };
+            result.push(Runnable::new(kind, name, target.as_ref(), line, ignored));
+        }
+        result
+    }
+
+    pub fn get_scopes(&self, file_id: FileId) -> Scopes {
+        let db = self.host.raw_database();
+        let sema = Semantics::new(db);
+        let source_file = sema.parse(file_id);
+        // the crate root has no path
+        let module_path = sema
+            .to_module_def(file_id)
+            .map(|module| {
+                let mut names: Vec<_> = module
+                    .path_to_root(db)
+                    .into_iter()
+                    .filter_map(|module| module.name(db))
+                    .map(|name| name.to_string())
+                    .collect();
+                names.reverse();
+                names.join("::")
+            })
+            .filter(|path| !path.is_empty());
+        Scopes::new(
+            source_file.syntax(),
+            &self.line_finder(file_id),
+            module_path.as_deref(),
+        )
+    }
+
+    pub fn get_metrics(&self, file_id: FileId) -> Vec<FunctionMetrics> {
+        let source_file = self
+            .host
+            .analysis()
+            .parse(file_id)
+            .expect("RA task cannot be cancelled");
+        function_metrics(source_file.syntax(), &self.line_finder(file_id))
+    }
+
+    pub fn get_outline(&self, file_id: FileId) -> Vec<OutlineNode> {
+        let structure = self
+            .host
+            .analysis()
+            .file_structure(file_id)
+            .expect("RA task cannot be cancelled");
+        OutlineNode::from_structure(structure, &self.line_finder(file_id))
+    }
+
+    /// Module trees of the crates rendered in the report
+    pub fn get_module_trees(&self, settings: &Settings) -> Vec<ModuleNode> {
+        let analysis = self.host.analysis();
+        module_trees(
+            self.host.raw_database(),
+            |file_id| self.all_files.contains(&file_id),
+            |frange| {
+                jump_from_frange(
+                    frange,
+                    &self.vfs,
+                    &self.external_crates,
+                    &analysis,
+                    settings,
+                )
+            },
+        )
+    }
+
     pub fn process_file(&self, file_id: FileId, settings: &Settings) -> Vec<HtmlToken> {
         let sema = Semantics::new(self.host.raw_database());
         let root = {
@@ -52,7 +196,35 @@ impl SyntaxProcessor {
             let source_file = source_file.syntax();
             source_file.clone()
         };
-        self.traverse_syntax(file_id, &root, settings)
+        self.traverse_syntax(&sema, file_id, &root, settings)
+    }
+
+    /// Whether the file belongs to a library crate, its public items are used by other crates
+    fn is_library(&self, file_id: FileId) -> bool {
+        let crate_graph = self.host.raw_database().crate_graph();
+        self.host
+            .analysis()
+            .crate_for(file_id)
+            .expect("RA task cannot be cancelled")
+            .iter()
+            .any(|crate_id| {
+                let root = self.vfs.file_path(crate_graph[*crate_id].root_file_id);
+                root.as_path()
+                    .map_or(false, |path| is_library_root(&self.packages, path.as_ref()))
+            })
+    }
+
+    /// Cargo target of the crate of the file, the first one if the file is shared by crates
+    fn cargo_target(&self, file_id: FileId) -> Option<CargoTarget> {
+        let crate_graph = self.host.raw_database().crate_graph();
+        let crate_id = *self
+            .host
+            .analysis()
+            .crate_for(file_id)
+            .expect("RA task cannot be cancelled")
+            .first()?;
+        let root = self.vfs.file_path(crate_graph[crate_id].root_file_id);
+        cargo_target(&self.packages, root.as_path()?.as_ref())
     }
 
     fn line_finder(&self, file_id: FileId) -> Arc<LineIndex> {
@@ -61,28 +233,23 @@ impl SyntaxProcessor {
 
     fn traverse_syntax(
         &self,
+        sema: &Semantics<'_, RootDatabase>,
         file_id: FileId,
         root: &SyntaxNode,
         settings: &Settings,
     ) -> Vec<HtmlToken> {
         let analysis = self.host.analysis();
         let search_scope = SearchScope::files(&self.all_files);
-        let highlight_config = HighlightConfig {
-            strings: false,
-            punctuation: false,
-            specialize_punctuation: false,
-            specialize_operator: false,
-            operator: false,
-            inject_doc_comment: false,
-            macro_bang: false,
-            syntactic_name_ref_highlighting: false,
-        };
-        let hl_map: HashMap<_, _> = analysis
+        let highlight_config = highlight_config(&settings.highlight);
+        let highlights = analysis
             .highlight(highlight_config, file_id)
-            .expect("RA task cannot be cancelled")
-            .into_iter()
-            .map(|r| (r.range, r.highlight))
-            .collect();
+            .expect("RA task cannot be cancelled");
+        let hl_map: HashMap<_, _> = highlights.iter().map(|r| (r.range, r.highlight)).collect();
+        let hover_config = HoverConfig {
+            links_in_hover: false,
+            documentation: None,
+            keywords: true,
+        };
 
         let inline_config = InlayHintsConfig {
             render_colons: false,
@@ -105,6 +272,8 @@ impl SyntaxProcessor {
             .into_iter()
             .map(|hint| (hint.range, hint))
             .collect();
+        let line_finder = self.line_finder(file_id);
+        let is_library = self.is_library(file_id);
 
         let mut result_tokens = vec![];
         for event in root.preorder_with_tokens() {
@@ -129,6 +298,28 @@ impl SyntaxProcessor {
                 result_tokens.extend(tokens);
                 continue;
             }
+            let task_kind = task_kind(&token);
+            if let Some(rest_class) = split_token_class(&token) {
+                let inside = highlights_inside(&highlights, range);
+                if inside.iter().any(|hl| hl.range != range) {
+                    let mut tokens = split_token(
+                        &token,
+                        inside,
+                        rest_class,
+                        file_id,
+                        &hover_config,
+                        &self.vfs,
+                        &self.external_crates,
+                        &analysis,
+                        settings,
+                    );
+                    if let Some(first) = tokens.first_mut() {
+                        first.task_kind = task_kind;
+                    }
+                    result_tokens.extend(tokens);
+                    continue;
+                }
+            }
             let highlight = highlight_class(&token, hl_map.get(&range).cloned());
             if is_string(&token) {
                 let tokens = parse_new_lines(
@@ -146,12 +337,6 @@ impl SyntaxProcessor {
                 offset: range.start(),
             };
 
-            let hover_config = HoverConfig {
-                links_in_hover: false,
-                documentation: None,
-                keywords: true,
-            };
-
             let kind = token.kind();
             let useless =
                 kind.is_literal() || kind.is_keyword() || kind.is_punct() || kind.is_trivia();
@@ -170,8 +355,46 @@ impl SyntaxProcessor {
             } else {
                 None
             };
-            let navigation =
-                navigation_from_search_results(ref_search, &self.vfs, &analysis, settings, frange);
+            let unused_item = ref_search.as_deref().and_then(|results| {
+                unused_item(sema, &token, results, file_id, is_library, &line_finder)
+            });
+            let highlight = match ref_search.as_deref() {
+                Some(results) if is_write_access(results, frange) => {
+                    highlight.map(|class| format!("{class} write_access"))
+                }
+                _ => highlight,
+            };
+            let mut navigation = navigation_from_search_results(
+                ref_search,
+                &self.vfs,
+                &self.external_crates,
+                &analysis,
+                settings,
+                frange,
+            );
+            if let Some(navigation) = navigation.as_mut() {
+                if is_fn_name(&token) {
+                    navigation.calls = Some(call_hierarchy(
+                        fposition,
+                        &self.vfs,
+                        &self.external_crates,
+                        &analysis,
+                        settings,
+                    ));
+                }
+            }
+
+            let macro_expansion = if is_macro_call_name(&token) {
+                analysis
+                    .expand_macro(fposition)
+                    .expect("RA task cannot be cancelled")
+                    .map(|expanded| MacroExpansion {
+                        html: highlight_expansion(&expanded.expansion),
+                        name: expanded.name,
+                    })
+            } else {
+                None
+            };
 
             let hover_info = {
                 if token.kind() == SK::COMMENT {
@@ -190,6 +413,10 @@ impl SyntaxProcessor {
                 hover_info,
                 type_info: type_map.get(&range).map(|h| h.label.to_string()),
                 navigation,
+                macro_expansion,
+                unsafe_kind: unsafe_kind(&token, hl_map.get(&range).copied()),
+                task_kind,
+                unused_item,
             };
 
             result_tokens.push(html_token);
@@ -201,6 +428,7 @@ impl SyntaxProcessor {
 fn jump_from_target(
     target: &NavigationTarget,
     vfs: &Vfs,
+    crates: &ExternalCrates,
     analysis: &Analysis,
     settings: &Settings,
 ) -> Option<JumpDestination> {
@@ -208,23 +436,25 @@ fn jump_from_target(
         file_id: target.file_id,
         range: target.focus_or_full_range(),
     };
-    jump_from_frange(frange, vfs, analysis, settings)
+    jump_from_frange(frange, vfs, crates, analysis, settings)
 }
 
 fn jump_from_frange(
     frange: FileRange,
     vfs: &Vfs,
+    crates: &ExternalCrates,
     analysis: &Analysis,
     settings: &Settings,
 ) -> Option<JumpDestination> {
     let file = vfs.file_path(frange.file_id);
+    let line_finder = analysis.file_line_index(frange.file_id).unwrap();
     let file_path =
         if let Ok(file_path) = relative_path(&file, &settings.dir, &settings.project_name) {
             file_path
         } else {
-            return None;
+            let location = JumpLocation::from_focus(&frange.range, line_finder);
+            return crates.jump(file.as_path()?.as_ref(), location);
         };
-    let line_finder = analysis.file_line_index(frange.file_id).unwrap();
 
     Some(JumpDestination::from_focus(
         file_path,
@@ -233,23 +463,21 @@ fn jump_from_frange(
     ))
 }
 
+/// Token where the navigation starts, `None` when its file is not rendered in the report
 fn jump_to_origin(
     frange: FileRange,
     vfs: &Vfs,
+    crates: &ExternalCrates,
     analysis: &Analysis,
     settings: &Settings,
-) -> JumpDestination {
-    let origin_file = vfs.file_path(frange.file_id);
-    let origin_file_path = relative_path(&origin_file, &settings.dir, &settings.project_name)
-        .expect("origin file should be relative path");
-    let origin_line_finder = analysis.file_line_index(frange.file_id).unwrap();
-    let origin_location = JumpLocation::from_focus(&frange.range, origin_line_finder);
-    JumpDestination::new(origin_file_path, origin_location)
+) -> Option<JumpDestination> {
+    jump_from_frange(frange, vfs, crates, analysis, settings)
 }
 
 fn ref_jumps_from_ra_search(
     maybe_results: &[ReferenceSearchResult],
     vfs: &Vfs,
+    crates: &ExternalCrates,
     analysis: &Analysis,
     settings: &Settings,
 ) -> Vec<JumpDestination> {
@@ -260,12 +488,13 @@ fn ref_jumps_from_ra_search(
                 .references
                 .iter()
                 .flat_map(|(file_id, refs)| {
-                    refs.iter().filter_map(move |(range, _maybe_category)| {
+                    refs.iter().filter_map(move |(range, maybe_category)| {
                         let frange = FileRange {
                             file_id: *file_id,
                             range: *range,
                         };
-                        jump_from_frange(frange, vfs, analysis, settings)
+                        jump_from_frange(frange, vfs, crates, analysis, settings)
+                            .map(|jump| jump.with_category(maybe_category.map(Into::into)))
                     })
                 })
                 .collect::<Vec<_>>()
@@ -273,9 +502,20 @@ fn ref_jumps_from_ra_search(
         .collect::<Vec<_>>()
 }
 
+fn is_write_access(results: &[ReferenceSearchResult], frange: FileRange) -> bool {
+    results
+        .iter()
+        .filter_map(|search_result| search_result.references.get(&frange.file_id))
+        .flatten()
+        .any(|(range, category)| {
+            *range == frange.range && matches!(category, Some(ReferenceCategory::Write))
+        })
+}
+
 fn def_jumps_from_ra_search(
     maybe_results: &[ReferenceSearchResult],
     vfs: &Vfs,
+    crates: &ExternalCrates,
     analysis: &Analysis,
     settings: &Settings,
 ) -> Vec<JumpDestination> {
@@ -285,29 +525,88 @@ fn def_jumps_from_ra_search(
             search_result
                 .declaration
                 .as_ref()
-                .and_then(|decl| jump_from_target(&decl.nav, vfs, analysis, settings))
+                .and_then(|decl| jump_from_target(&decl.nav, vfs, crates, analysis, settings))
+        })
+        .fold(vec![], |mut definitions, definition| {
+            if !definitions.contains(&definition) {
+                definitions.push(definition);
+            }
+            definitions
         })
-        .collect()
 }
 
 fn navigation_from_search_results(
     maybe_results: Option<Vec<ReferenceSearchResult>>,
     vfs: &Vfs,
+    crates: &ExternalCrates,
     analysis: &Analysis,
     settings: &Settings,
     origin_frange: FileRange,
 ) -> Option<Navigation> {
     let maybe_results = maybe_results.unwrap_or_default();
-    let references = ref_jumps_from_ra_search(maybe_results.as_ref(), vfs, analysis, settings);
-    let definitions = def_jumps_from_ra_search(maybe_results.as_ref(), vfs, analysis, settings);
-    let from = jump_to_origin(origin_frange, vfs, analysis, settings);
-    definitions.first().map(|definition| Navigation {
-        definition: definition.to_owned(),
+    let references =
+        ref_jumps_from_ra_search(maybe_results.as_ref(), vfs, crates, analysis, settings);
+    let definitions =
+        def_jumps_from_ra_search(maybe_results.as_ref(), vfs, crates, analysis, settings);
+    if definitions.is_empty() && references.is_empty() {
+        return None;
+    }
+    let from = jump_to_origin(origin_frange, vfs, crates, analysis, settings)?;
+    Some(Navigation {
+        definitions,
         references,
         from,
+        calls: None,
     })
 }
 
+fn call_hierarchy(
+    position: FilePosition,
+    vfs: &Vfs,
+    crates: &ExternalCrates,
+    analysis: &Analysis,
+    settings: &Settings,
+) -> CallHierarchy {
+    let incoming = analysis
+        .incoming_calls(position)
+        .expect("RA task cannot be cancelled")
+        .unwrap_or_default();
+    let outgoing = analysis
+        .outgoing_calls(position)
+        .expect("RA task cannot be cancelled")
+        .unwrap_or_default();
+    CallHierarchy {
+        // callers are shown at the first call site inside of them
+        incoming: incoming
+            .iter()
+            .filter_map(|call| {
+                let frange = FileRange {
+                    file_id: call.target.file_id,
+                    range: *call.ranges.first()?,
+                };
+                jump_from_frange(frange, vfs, crates, analysis, settings)
+                    .map(|destination| call_jump(call, destination))
+            })
+            .collect(),
+        // callees are shown at their definition
+        outgoing: outgoing
+            .iter()
+            .filter_map(|call| {
+                jump_from_target(&call.target, vfs, crates, analysis, settings)
+                    .map(|destination| call_jump(call, destination))
+            })
+            .collect(),
+    }
+}
+
+fn call_jump(call: &CallItem, destination: JumpDestination) -> CallJump {
+    CallJump {
+        name: call.target.name.to_string(),
+        count: call.ranges.len(),
+        destination,
+    }
+}
+
 fn highlight_class(token: &SyntaxToken, ra_highlight: Option<Highlight>) -> Option<String> {
     if let Some(hl) = ra_highlight {
         Some(hl.to_string().replace('.', " "))
@@ -318,15 +617,363 @@ fn highlight_class(token: &SyntaxToken, ra_highlight: Option<Highlight>) -> Opti
     }
 }
 
+/// `unsafe` keywords of blocks, functions, impls and traits,
+/// and operations highlighted as unsafe by rust-analyzer
+fn unsafe_kind(syntax_token: &SyntaxToken, highlight: Option<Highlight>) -> Option<UnsafeKind> {
+    if syntax_token.kind() == SK::UNSAFE_KW {
+        return match syntax_token.parent()?.kind() {
+            SK::BLOCK_EXPR => Some(UnsafeKind::Block),
+            SK::FN => Some(UnsafeKind::Fn),
+            SK::IMPL => Some(UnsafeKind::Impl),
+            SK::TRAIT => Some(UnsafeKind::Trait),
+            _ => None,
+        };
+    }
+    highlight
+        .filter(|highlight| highlight.mods.contains(HlMod::Unsafe))
+        .map(|_| UnsafeKind::Operation)
+}
+
+/// Item named by the token which has no references outside of it within the report.
+/// Items exported by library crates are left out, other crates may use them
+fn unused_item(
+    sema: &Semantics<'_, RootDatabase>,
+    syntax_token: &SyntaxToken,
+    results: &[ReferenceSearchResult],
+    file_id: FileId,
+    is_library: bool,
+    finder: &LineIndex,
+) -> Option<UnusedItem> {
+    let name = syntax_token.parent().and_then(ast::Name::cast)?;
+    let item = name.syntax().parent()?;
+    // methods of traits are exported by their trait, methods of trait impls are always used
+    let owner = item
+        .parent()
+        .filter(|parent| ast::AssocItemList::can_cast(parent.kind()))
+        .and_then(|parent| parent.parent());
+    let in_trait = owner
+        .as_ref()
+        .map_or(false, |owner| owner.kind() == SK::TRAIT);
+    let in_trait_impl = owner
+        .clone()
+        .and_then(ast::Impl::cast)
+        .map_or(false, |it| it.trait_().is_some());
+    let kind = match item.kind() {
+        SK::FN if in_trait_impl || syntax_token.text() == "main" => return None,
+        SK::FN if in_trait => ItemKind::TraitMethod,
+        SK::FN => ItemKind::Fn,
+        SK::STRUCT => ItemKind::Struct,
+        SK::CONST => ItemKind::Const,
+        _ => return None,
+    };
+    let exported = || {
+        let imports: Vec<_> = results
+            .iter()
+            .flat_map(|search_result| search_result.references.iter())
+            .flat_map(|(ref_file_id, refs)| {
+                refs.iter()
+                    .filter(|(_, category)| matches!(category, Some(ReferenceCategory::Import)))
+                    .map(|(range, _)| (*ref_file_id, *range))
+            })
+            .collect();
+        match &owner {
+            // methods are exported with their trait
+            Some(owner) if kind == ItemKind::TraitMethod => is_exported(sema, owner, &[]),
+            _ => is_exported(sema, &item, &imports),
+        }
+    };
+    if has_entry_attr(&item) || (is_library && exported()) {
+        return None;
+    }
+    let item_range = item.text_range();
+    let used = results
+        .iter()
+        .flat_map(|search_result| search_result.references.iter())
+        .any(|(ref_file_id, refs)| {
+            refs.iter()
+                .any(|(range, _)| *ref_file_id != file_id || !item_range.contains_range(*range))
+        });
+    if used {
+        return None;
+    }
+    let line = |offset| finder.line_col(offset).line + 1;
+    Some(UnusedItem {
+        kind,
+        name: syntax_token.text().to_string(),
+        line: line(syntax_token.text_range().start()),
+        start_line: line(item_range.start()),
+        end_line: line(item_range.end()),
+    })
+}
+
+/// Whether other crates can reach the item: it is `pub` in a module which is reachable
+/// from the crate root, or one of `imports` is a `pub use` of such a module
+fn is_exported(
+    sema: &Semantics<'_, RootDatabase>,
+    item: &SyntaxNode,
+    imports: &[(FileId, TextRange)],
+) -> bool {
+    let db = sema.db;
+    let public = |visibility: Visibility, module: Module| {
+        visibility == Visibility::Public && is_reachable(db, module)
+    };
+    let declared = match_ast! {
+        match item {
+            ast::Fn(it) => sema.to_def(&it).map(|def| public(def.visibility(db), def.module(db))),
+            ast::Struct(it) => sema.to_def(&it).map(|def| public(def.visibility(db), def.module(db))),
+            ast::Const(it) => sema.to_def(&it).map(|def| public(def.visibility(db), def.module(db))),
+            ast::Trait(it) => sema.to_def(&it).map(|def| public(def.visibility(db), def.module(db))),
+            _ => None,
+        }
+    };
+    if declared == Some(true) {
+        return true;
+    }
+    imports.iter().any(|(file_id, range)| {
+        let root = sema.parse(*file_id);
+        let node = match root.syntax().covering_element(*range) {
+            NodeOrToken::Node(node) => Some(node),
+            NodeOrToken::Token(token) => token.parent(),
+        };
+        let use_item = match node.and_then(|node| node.ancestors().find_map(ast::Use::cast)) {
+            Some(use_item) => use_item,
+            None => return false,
+        };
+        let is_pub = use_item
+            .visibility()
+            .map_or(false, |visibility| visibility.syntax().text() == "pub");
+        let module = match use_item.syntax().ancestors().find_map(ast::Module::cast) {
+            Some(module) => sema.to_def(&module),
+            None => sema.to_module_def(*file_id),
+        };
+        is_pub && module.map_or(false, |module| is_reachable(db, module))
+    })
+}
+
+/// Whether the module and all of its parents are public, the crate root is always reachable
+fn is_reachable(db: &RootDatabase, module: Module) -> bool {
+    module
+        .path_to_root(db)
+        .into_iter()
+        .all(|module| module.parent(db).is_none() || module.visibility(db) == Visibility::Public)
+}
+
+/// Tests, benchmarks and exported symbols are called from outside of the code
+fn has_entry_attr(item: &SyntaxNode) -> bool {
+    item.children().filter_map(ast::Attr::cast).any(|attr| {
+        let name = attr
+            .path()
+            .and_then(|path| path.segment())
+            .map(|segment| segment.syntax().text().to_string());
+        matches!(
+            name.as_deref(),
+            Some(
+                "test"
+                    | "bench"
+                    | "main"
+                    | "no_mangle"
+                    | "export_name"
+                    | "proc_macro"
+                    | "proc_macro_derive"
+                    | "proc_macro_attribute"
+            )
+        )
+    })
+}
+
+fn task_kind(syntax_token: &SyntaxToken) -> Option<TaskKind> {
+    if syntax_token.kind() == SK::COMMENT {
+        return comment_task_kind(syntax_token.text());
+    }
+    if !is_macro_call_name(syntax_token) {
+        return None;
+    }
+    match syntax_token.text() {
+        "todo" => Some(TaskKind::TodoMacro),
+        "unimplemented" => Some(TaskKind::Unimplemented),
+        "unreachable" => Some(TaskKind::Unreachable),
+        "panic" => Some(TaskKind::Panic),
+        _ => None,
+    }
+}
+
+/// Finds the first `TODO`, `FIXME` or `HACK` word of the comment
+fn comment_task_kind(text: &str) -> Option<TaskKind> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .find_map(|word| match word {
+            "TODO" => Some(TaskKind::Todo),
+            "FIXME" => Some(TaskKind::Fixme),
+            "HACK" => Some(TaskKind::Hack),
+            _ => None,
+        })
+}
+
+fn is_fn_name(syntax_token: &SyntaxToken) -> bool {
+    syntax_token
+        .parent()
+        .and_then(ast::Name::cast)
+        .and_then(|name| name.syntax().parent())
+        .map_or(false, |parent| ast::Fn::can_cast(parent.kind()))
+}
+
+/// Checks that the token is the name of a macro call (`name!(..)`)
+/// or the `derive` of a derive attribute.
+fn is_macro_call_name(syntax_token: &SyntaxToken) -> bool {
+    let path = match syntax_token
+        .parent()
+        .and_then(ast::NameRef::cast)
+        .and_then(|name_ref| name_ref.syntax().parent())
+        .and_then(ast::PathSegment::cast)
+    {
+        Some(segment) => segment.parent_path(),
+        None => return false,
+    };
+    let parent = match path.syntax().parent() {
+        Some(parent) => parent,
+        None => return false,
+    };
+    ast::MacroCall::can_cast(parent.kind())
+        || (path.syntax().text() == "derive"
+            && parent
+                .ancestors()
+                .any(|node| ast::Attr::can_cast(node.kind())))
+}
+
+fn highlight_config(options: &HighlightOptions) -> HighlightConfig {
+    HighlightConfig {
+        strings: options.strings,
+        punctuation: options.punctuation,
+        specialize_punctuation: options.specialize_punctuation,
+        specialize_operator: options.specialize_operators,
+        operator: options.operators,
+        inject_doc_comment: true,
+        macro_bang: options.macro_bang,
+        syntactic_name_ref_highlighting: options.syntactic_name_refs,
+    }
+}
+
+/// Tokens which may contain ranges highlighted by rust-analyzer:
+/// injected code of doc comments, escape sequences and format arguments of strings.
+/// Returns the class of the rest of the token.
+fn split_token_class(syntax_token: &SyntaxToken) -> Option<&'static str> {
+    if is_string(syntax_token) {
+        Some("string_literal")
+    } else if ast::Comment::cast(syntax_token.clone()).map_or(false, |comment| comment.is_doc()) {
+        Some("comment documentation")
+    } else {
+        None
+    }
+}
+
+/// Highlighted ranges which start inside of `range`, `highlights` are sorted by offset.
+fn highlights_inside(highlights: &[HlRange], range: TextRange) -> &[HlRange] {
+    let start = highlights.partition_point(|hl| hl.range.start() < range.start());
+    let end = highlights.partition_point(|hl| hl.range.start() < range.end());
+    &highlights[start..end]
+}
+
+/// Splits the token into the ranges highlighted by rust-analyzer,
+/// injected ranges (code of examples and intra-doc links) get hover and navigation.
+#[allow(clippy::too_many_arguments)]
+fn split_token(
+    token: &SyntaxToken,
+    highlights: &[HlRange],
+    rest_class: &str,
+    file_id: FileId,
+    hover_config: &HoverConfig,
+    vfs: &Vfs,
+    crates: &ExternalCrates,
+    analysis: &Analysis,
+    settings: &Settings,
+) -> Vec<HtmlToken> {
+    let token_range = token.text_range();
+    let text = |range: TextRange| &token.text()[range - token_range.start()];
+    let rest_class = || Some(rest_class.to_string());
+    let mut tokens = vec![];
+    let mut position = token_range.start();
+    for hl in highlights {
+        if position < hl.range.start() {
+            let gap = TextRange::new(position, hl.range.start());
+            tokens.extend(parse_new_lines(text(gap), gap.start().into(), rest_class()));
+        }
+        position = hl.range.end();
+        let highlight = highlight_class(token, Some(hl.highlight));
+        if !hl.highlight.mods.contains(HlMod::Injected) || hl.highlight.tag == HlTag::Comment {
+            tokens.extend(parse_new_lines(
+                text(hl.range),
+                hl.range.start().into(),
+                highlight,
+            ));
+            continue;
+        }
+        let frange = FileRange {
+            file_id,
+            range: hl.range,
+        };
+        let fposition = FilePosition {
+            file_id,
+            offset: hl.range.start(),
+        };
+        let definitions: Vec<_> = analysis
+            .goto_definition(fposition)
+            .expect("RA task cannot be cancelled")
+            .map(|targets| {
+                targets
+                    .info
+                    .iter()
+                    .filter_map(|target| jump_from_target(target, vfs, crates, analysis, settings))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let navigation = if definitions.is_empty() {
+            None
+        } else {
+            jump_to_origin(frange, vfs, crates, analysis, settings).map(|from| Navigation {
+                definitions,
+                references: vec![],
+                from,
+                calls: None,
+            })
+        };
+        let hover_range = FileRange {
+            file_id,
+            range: TextRange::empty(hl.range.start()),
+        };
+        tokens.push(HtmlToken {
+            is_new_line: false,
+            range: hl.range,
+            highlight,
+            hover_info: analysis
+                .hover(hover_config, hover_range)
+                .expect("RA task cannot be cancelled")
+                .map(|r| r.info),
+            type_info: None,
+            navigation,
+            macro_expansion: None,
+            unsafe_kind: None,
+            task_kind: None,
+            unused_item: None,
+        });
+    }
+    if position < token_range.end() {
+        let gap = TextRange::new(position, token_range.end());
+        tokens.extend(parse_new_lines(text(gap), gap.start().into(), rest_class()));
+    }
+    tokens
+}
+
 fn is_new_line(syntax_token: &SyntaxToken) -> bool {
     syntax_token.kind() == SK::WHITESPACE && syntax_token.text().contains('\n')
 }
 
 fn is_string(syntax_token: &SyntaxToken) -> bool {
-    syntax_token.kind() == SK::STRING
+    matches!(
+        syntax_token.kind(),
+        SK::STRING | SK::BYTE_STRING | SK::C_STRING
+    )
 }
 
-fn parse_new_lines(text: &str, from: u32, highlight: Option<String>) -> Vec<HtmlToken> {
+pub(crate) fn parse_new_lines(text: &str, from: u32, highlight: Option<String>) -> Vec<HtmlToken> {
     let len = text.split('\n').count();
 
     let mut shift = 0;
@@ -365,6 +1012,8 @@ fn relative_path(vfs: &VfsPath, root: &Path, project_name: &str) -> Result<Strin
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ide_db::base_db::fixture::WithFixture;
+    use syntax::ast::HasName;
 
     fn range(start: u32, end: u32) -> TextRange {
         TextRange::new(start.into(), end.into())
@@ -415,4 +1064,63 @@ mod tests {
             assert_eq!(actual, expected)
         }
     }
+
+    #[test]
+    fn test_comment_task_kind() {
+        assert_eq!(
+            comment_task_kind("// TODO: handle errors"),
+            Some(TaskKind::Todo)
+        );
+        assert_eq!(
+            comment_task_kind("/* FIXME(someone) HACK */"),
+            Some(TaskKind::Fixme)
+        );
+        assert_eq!(comment_task_kind("/// HACK"), Some(TaskKind::Hack));
+        assert_eq!(comment_task_kind("// TODOS and todo are not markers"), None);
+        assert_eq!(comment_task_kind("// MY_TODO"), None);
+    }
+
+    #[test]
+    fn test_is_exported() {
+        let (db, file_id) = RootDatabase::with_single_file(
+            r#"
+mod private {
+    pub fn hidden() {}
+    pub fn reexported() {}
+}
+pub mod public {
+    pub fn visible() {}
+    pub(crate) fn internal() {}
+}
+pub use private::reexported;
+"#,
+        );
+        let sema = Semantics::new(&db);
+        let root = sema.parse(file_id);
+        let function = |name: &str| {
+            root.syntax()
+                .descendants()
+                .filter_map(ast::Fn::cast)
+                .find(|function| function.name().map_or(false, |it| it.text() == name))
+                .unwrap()
+        };
+        let import = root
+            .syntax()
+            .descendants()
+            .filter_map(ast::NameRef::cast)
+            .find(|name_ref| name_ref.text() == "reexported")
+            .unwrap();
+        let exported = |name: &str, imports: &[(FileId, TextRange)]| {
+            is_exported(&sema, function(name).syntax(), imports)
+        };
+        // `pub` items of a private module are not reachable from other crates
+        assert!(!exported("hidden", &[]));
+        assert!(!exported("reexported", &[]));
+        assert!(exported(
+            "reexported",
+            &[(file_id, import.syntax().text_range())]
+        ));
+        assert!(exported("visible", &[]));
+        assert!(!exported("internal", &[]));
+    }
 }
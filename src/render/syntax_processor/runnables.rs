@@ -0,0 +1,360 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::Serialize;
+use syntax::{TextRange, TextSize};
+
+use crate::metadata::Package;
+
+pub type RunCommands = HashMap<u32, Vec<RunCommand>>;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum RunKind {
+    Test,
+    TestMod,
+    Bench,
+    DocTest,
+    Bin,
+}
+
+/// Test, benchmark, doctest or binary found by rust-analyzer
+#[derive(Serialize, Clone, Debug)]
+pub struct Runnable {
+    pub kind: RunKind,
+    /// Path of the test from the crate root, or the name of the binary
+    pub name: String,
+    pub package: Option<String>,
+    pub line: u32,
+    pub ignored: bool,
+    pub command: RunCommand,
+}
+
+impl Runnable {
+    pub fn new(
+        kind: RunKind,
+        name: String,
+        target: Option<&CargoTarget>,
+        line: u32,
+        ignored: bool,
+    ) -> Self {
+        let command = RunCommand::new(kind, &name, target, ignored);
+        Self {
+            kind,
+            name,
+            package: target.map(|target| target.package.clone()),
+            line,
+            ignored,
+            command,
+        }
+    }
+}
+
+/// Cargo target of a crate root, selects the crate in the commands
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CargoTarget {
+    pub package: String,
+    pub kind: TargetKind,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetKind {
+    Lib,
+    Bin,
+    Test,
+    Bench,
+    Example,
+    /// Build script or a target which is not in the metadata
+    Other,
+}
+
+impl TargetKind {
+    fn new(kinds: &[String]) -> Self {
+        let kind = kinds.first().map_or("", String::as_str);
+        match kind {
+            "lib" | "rlib" | "dylib" | "cdylib" | "staticlib" | "proc-macro" => TargetKind::Lib,
+            "bin" => TargetKind::Bin,
+            "test" => TargetKind::Test,
+            "bench" => TargetKind::Bench,
+            "example" => TargetKind::Example,
+            _ => TargetKind::Other,
+        }
+    }
+}
+
+impl CargoTarget {
+    /// `--package <package>` with the option selecting the target, e.g. `--test <name>`
+    fn args(&self) -> String {
+        let target = match self.kind {
+            TargetKind::Lib => " --lib".to_string(),
+            TargetKind::Bin => format!(" --bin {}", self.name),
+            TargetKind::Test => format!(" --test {}", self.name),
+            TargetKind::Bench => format!(" --bench {}", self.name),
+            TargetKind::Example => format!(" --example {}", self.name),
+            TargetKind::Other => String::new(),
+        };
+        format!(" --package {}{target}", self.package)
+    }
+}
+
+/// Target of the crate root file in the package which manifest is the closest to the file
+pub fn cargo_target(packages: &[Package], root: &Path) -> Option<CargoTarget> {
+    let package = packages
+        .iter()
+        .filter(|package| {
+            package
+                .manifest_path
+                .parent()
+                .map_or(false, |dir| root.starts_with(dir))
+        })
+        .max_by_key(|package| package.manifest_path.components().count())?;
+    let target = package
+        .targets
+        .iter()
+        .find(|target| target.src_path == root);
+    Some(CargoTarget {
+        package: package.name.clone(),
+        kind: target.map_or(TargetKind::Other, |target| TargetKind::new(&target.kind)),
+        name: target.map_or_else(String::new, |target| target.name.clone()),
+    })
+}
+
+/// Shell command shown in the gutter of a line, copied to the clipboard on click.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct RunCommand {
+    pub title: String,
+    pub command: String,
+}
+
+impl RunCommand {
+    fn new(kind: RunKind, name: &str, target: Option<&CargoTarget>, ignored: bool) -> Self {
+        let package = target.map_or(String::new(), |target| {
+            format!(" --package {}", target.package)
+        });
+        let args = target.map_or(String::new(), CargoTarget::args);
+        let ignored = if ignored { " --ignored" } else { "" };
+        let (title, command) = match kind {
+            RunKind::Test => (
+                format!("Run test {name}"),
+                format!("cargo test{args} -- {name} --exact{ignored}"),
+            ),
+            RunKind::TestMod => (
+                format!("Run tests of {name}"),
+                format!("cargo test{args} -- {name}"),
+            ),
+            RunKind::Bench => (
+                format!("Run benchmark {name}"),
+                format!("cargo bench{args} -- {name} --exact"),
+            ),
+            RunKind::DocTest => (
+                format!("Run doctest of {name}"),
+                format!("cargo test --doc{package} -- {name}"),
+            ),
+            RunKind::Bin if name.is_empty() => ("Run binary".to_string(), "cargo run".to_string()),
+            // the target of the binary is known from the metadata
+            RunKind::Bin if target.map_or(false, |target| target.kind != TargetKind::Other) => {
+                (format!("Run binary {name}"), format!("cargo run{args}"))
+            }
+            RunKind::Bin => (
+                format!("Run binary {name}"),
+                format!("cargo run{package} --bin {name}"),
+            ),
+        };
+        Self { title, command }
+    }
+}
+
+pub fn run_commands(runnables: &[Runnable]) -> RunCommands {
+    let mut commands = RunCommands::new();
+    for runnable in runnables {
+        let line_commands = commands.entry(runnable.line).or_default();
+        line_commands.push(runnable.command.clone());
+    }
+    commands
+}
+
+/// Name of a binary target from `src/bin`, the default binary of the package has no name
+pub fn bin_name(path: &Path) -> Option<String> {
+    let parent = path.parent()?;
+    if parent.file_name()? == "bin" {
+        return Some(path.file_stem()?.to_string_lossy().to_string());
+    }
+    if path.file_name()? == "main.rs" && parent.parent()?.file_name()? == "bin" {
+        return Some(parent.file_name()?.to_string_lossy().to_string());
+    }
+    None
+}
+
+/// Whether the crate root belongs to a library, other targets are not used by other crates.
+/// Targets of the workspace are known from the metadata, the kind of the rest is guessed by the path
+pub fn is_library_root(packages: &[Package], path: &Path) -> bool {
+    match cargo_target(packages, path) {
+        Some(target) if target.kind != TargetKind::Other => return target.kind == TargetKind::Lib,
+        _ => (),
+    }
+    let is_other_target = path
+        .parent()
+        .and_then(Path::file_name)
+        .map_or(false, |dir| {
+            dir == "examples" || dir == "tests" || dir == "benches"
+        });
+    path.file_name()
+        .map_or(false, |name| name != "main.rs" && name != "build.rs")
+        && bin_name(path).is_none()
+        && !is_other_target
+}
+
+/// Offsets of the opening fences of rust code blocks in doc comments inside of `range`.
+pub fn doctest_starts(text: &str, range: TextRange) -> Vec<TextSize> {
+    let mut starts = vec![];
+    let mut in_code = false;
+    let mut offset = usize::from(range.start());
+    for line in text[range].split_inclusive('\n') {
+        let line_offset = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        let doc = match trimmed
+            .strip_prefix("///")
+            .or_else(|| trimmed.strip_prefix("//!"))
+        {
+            Some(doc) => doc.trim(),
+            None => continue,
+        };
+        let fence = match doc.strip_prefix("```") {
+            Some(fence) => fence,
+            None => continue,
+        };
+        if !in_code && is_rust_block(fence) {
+            starts.push(TextSize::from(
+                (line_offset + line.len() - trimmed.len()) as u32,
+            ));
+        }
+        in_code = !in_code;
+    }
+    starts
+}
+
+/// Code blocks without a language or with rustdoc attributes are doctests.
+fn is_rust_block(info: &str) -> bool {
+    info.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|attribute| !attribute.is_empty())
+        .all(|attribute| {
+            matches!(
+                attribute,
+                "rust" | "should_panic" | "no_run" | "ignore" | "compile_fail" | "test_harness"
+            ) || attribute.starts_with("edition")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doctest_starts() {
+        let text = "/// Adds one.\n/// ```\n/// assert_eq!(add(1), 2);\n/// ```\n/// ```text\n/// ```\n    /// ```no_run\nfn add(x: u32) -> u32 { x + 1 }\n";
+        let range = TextRange::new(0.into(), (text.len() as u32).into());
+        let starts: Vec<_> = doctest_starts(text, range)
+            .into_iter()
+            .map(|start| &text[usize::from(start)..usize::from(start) + 9])
+            .collect();
+        assert_eq!(starts, vec!["/// ```\n/", "/// ```no"]);
+    }
+
+    #[test]
+    fn test_is_library_root() {
+        // without metadata the kind of the target is guessed by the path
+        let guessed = |path: &str| is_library_root(&[], Path::new(path));
+        assert!(guessed("/p/src/lib.rs"));
+        assert!(guessed("/p/crates/core/lib.rs"));
+        assert!(!guessed("/p/src/main.rs"));
+        assert!(!guessed("/p/src/bin/tool.rs"));
+        assert!(!guessed("/p/tests/integration.rs"));
+        assert!(!guessed("/p/examples/demo.rs"));
+        assert!(!guessed("/p/build.rs"));
+    }
+
+    #[test]
+    fn test_run_commands() {
+        let package = |name: &str, targets: &[(&str, &str, &str)]| Package {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            description: None,
+            authors: vec![],
+            license: None,
+            repository: None,
+            manifest_path: format!("/p/{name}/Cargo.toml").into(),
+            manifest: String::new(),
+            targets: targets
+                .iter()
+                .map(|(kind, name, path)| crate::metadata::Target {
+                    name: name.to_string(),
+                    kind: vec![kind.to_string()],
+                    src_path: path.into(),
+                })
+                .collect(),
+        };
+        let packages = [
+            package(
+                "foo-bar",
+                &[
+                    ("lib", "foo_bar", "/p/foo-bar/src/lib.rs"),
+                    ("bin", "tool", "/p/foo-bar/src/bin/tool.rs"),
+                    ("test", "it", "/p/foo-bar/tests/it.rs"),
+                    ("bin", "cli", "/p/foo-bar/src/cli.rs"),
+                ],
+            ),
+            package("foo", &[]),
+        ];
+        let command = |kind, name: &str, root: &str| {
+            let target = cargo_target(&packages, Path::new(root));
+            RunCommand::new(kind, name, target.as_ref(), false).command
+        };
+        assert_eq!(
+            command(RunKind::Bin, "tool", "/p/foo-bar/src/bin/tool.rs"),
+            "cargo run --package foo-bar --bin tool"
+        );
+        assert_eq!(
+            command(RunKind::Test, "api::works", "/p/foo-bar/tests/it.rs"),
+            "cargo test --package foo-bar --test it -- api::works --exact"
+        );
+        assert_eq!(
+            command(RunKind::TestMod, "tests", "/p/foo-bar/src/lib.rs"),
+            "cargo test --package foo-bar --lib -- tests"
+        );
+        assert_eq!(
+            command(RunKind::DocTest, "Foo", "/p/foo-bar/src/lib.rs"),
+            "cargo test --doc --package foo-bar -- Foo"
+        );
+        assert_eq!(
+            command(RunKind::Test, "t", "/p/foo/src/lib.rs"),
+            "cargo test --package foo -- t --exact"
+        );
+        assert_eq!(
+            command(RunKind::Bin, "tool", "/other/src/bin/tool.rs"),
+            "cargo run --bin tool"
+        );
+        // `[[bin]] path = "src/cli.rs"` is not a library despite its path
+        assert!(!is_library_root(
+            &packages,
+            Path::new("/p/foo-bar/src/cli.rs")
+        ));
+        assert!(is_library_root(
+            &packages,
+            Path::new("/p/foo-bar/src/lib.rs")
+        ));
+    }
+
+    #[test]
+    fn test_bin_name() {
+        assert_eq!(bin_name(Path::new("/p/src/main.rs")), None);
+        assert_eq!(
+            bin_name(Path::new("/p/src/bin/tool.rs")),
+            Some("tool".into())
+        );
+        assert_eq!(
+            bin_name(Path::new("/p/src/bin/tool/main.rs")),
+            Some("tool".into())
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use ide::LineIndex;
+use serde::Serialize;
+use syntax::{
+    ast::{self, HasName},
+    match_ast, AstNode, SyntaxNode,
+    WalkEvent::{Enter, Leave},
+};
+
+/// Item which encloses lines of the file: module, impl, trait, function, etc.
+#[derive(Debug, Serialize, Clone)]
+pub struct Scope {
+    pub label: String,
+    pub parent: Option<usize>,
+}
+
+/// Scopes of the file and the innermost scope of every line, used for breadcrumbs
+#[derive(Debug, Default, Clone)]
+pub struct Scopes {
+    pub scopes: Vec<Scope>,
+    lines: Vec<Option<usize>>,
+}
+
+impl Scopes {
+    /// `module_path` is the path of the file module, e.g. `render::generators`,
+    /// which encloses the scopes of the file
+    pub fn new(root: &SyntaxNode, finder: &LineIndex, module_path: Option<&str>) -> Self {
+        let line_count = finder.line_col(root.text_range().end()).line as usize + 1;
+        let mut result = Self {
+            scopes: vec![],
+            lines: vec![None; line_count],
+        };
+        let mut stack = vec![];
+        if let Some(path) = module_path {
+            result.scopes.push(Scope {
+                label: format!("mod {path}"),
+                parent: None,
+            });
+            result.lines.fill(Some(0));
+            stack.push((0, root.clone()));
+        }
+        for event in root.preorder() {
+            match event {
+                Enter(node) => {
+                    if let Some(label) = scope_label(&node) {
+                        let index = result.scopes.len();
+                        result.scopes.push(Scope {
+                            label,
+                            parent: stack.last().map(|(parent, _)| *parent),
+                        });
+                        let range = node.text_range();
+                        stack.push((index, node));
+                        let start = finder.line_col(range.start()).line as usize;
+                        let end = finder.line_col(range.end()).line as usize;
+                        // nested scopes are entered later and take their lines over
+                        for line in &mut result.lines[start..=end] {
+                            *line = Some(index);
+                        }
+                    }
+                }
+                Leave(node) => {
+                    if stack.last().map_or(false, |(_, scope)| *scope == node) {
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Innermost scope of the line, numbered from 1
+    pub fn line_scope(&self, line: usize) -> Option<usize> {
+        self.lines.get(line - 1).copied().flatten()
+    }
+}
+
+fn scope_label(node: &SyntaxNode) -> Option<String> {
+    let label = match_ast! {
+        match node {
+            ast::Module(it) => format!("mod {}", it.name()?),
+            ast::Impl(it) => {
+                let self_ty = it.self_ty()?;
+                match it.trait_() {
+                    Some(trait_) => format!("impl {} for {}", trait_.syntax(), self_ty.syntax()),
+                    None => format!("impl {}", self_ty.syntax()),
+                }
+            },
+            ast::Trait(it) => format!("trait {}", it.name()?),
+            ast::Fn(it) => format!("fn {}", it.name()?),
+            ast::Struct(it) => format!("struct {}", it.name()?),
+            ast::Enum(it) => format!("enum {}", it.name()?),
+            ast::Union(it) => format!("union {}", it.name()?),
+            ast::MacroRules(it) => format!("macro_rules! {}", it.name()?),
+            _ => return None,
+        }
+    };
+    Some(label.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes() {
+        let text = "use std::fmt;\nimpl S {\n    fn f() {}\n}\n";
+        let file = ast::SourceFile::parse(text).tree();
+        let scopes = Scopes::new(file.syntax(), &LineIndex::new(text), Some("foo::bar"));
+        let path = |line: usize| {
+            let mut path = vec![];
+            let mut scope = scopes.line_scope(line);
+            while let Some(index) = scope {
+                path.insert(0, scopes.scopes[index].label.as_str());
+                scope = scopes.scopes[index].parent;
+            }
+            path
+        };
+        assert_eq!(path(1), vec!["mod foo::bar"]);
+        assert_eq!(path(2), vec!["mod foo::bar", "impl S"]);
+        assert_eq!(path(3), vec!["mod foo::bar", "impl S", "fn f"]);
+
+        let scopes = Scopes::new(file.syntax(), &LineIndex::new(text), None);
+        assert_eq!(scopes.line_scope(1), None);
+        assert_eq!(scopes.scopes[0].label, "impl S");
+    }
+}
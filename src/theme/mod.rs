@@ -0,0 +1,407 @@
+mod textmate;
+mod vscode;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Themes shipped with the report, each one is `css/themes/<name>.css`
+pub const BUILTIN_THEMES: [&str; 3] = ["dark", "light", "high-contrast"];
+
+/// Themes available in the switcher of the report
+#[derive(Debug, Default, Clone)]
+pub struct Themes {
+    /// Value of `data-theme` selected when the reader has not chosen a theme
+    pub default: String,
+    pub options: Vec<ThemeOption>,
+    /// Style of the user theme, appended after the built-in ones
+    pub css: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeOption {
+    /// Names of the theme and of the theme it extends, matched by `[data-theme~="name"]`
+    pub value: String,
+    pub title: String,
+}
+
+/// Theme of the user which overrides colors of a built-in one
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserTheme {
+    pub name: String,
+    /// Built-in theme providing everything the theme does not set
+    #[serde(default = "default_base")]
+    pub base: String,
+    /// Variables of the palette without `--`, e.g. `bg`, `fg` or `blue`
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+    /// Styles of the highlight classes, tags like `function` optionally
+    /// with modifiers like `function.unsafe` or `variable.mutable`
+    #[serde(default)]
+    pub classes: BTreeMap<String, ClassStyle>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ClassStyle {
+    Color(String),
+    Style {
+        color: Option<String>,
+        background: Option<String>,
+        #[serde(default)]
+        bold: bool,
+        #[serde(default)]
+        italic: bool,
+        #[serde(default)]
+        underline: bool,
+    },
+}
+
+fn default_base() -> String {
+    "dark".to_string()
+}
+
+impl Themes {
+    pub fn new(theme: &str, theme_file: Option<&Path>) -> Result<Self, anyhow::Error> {
+        let mut options: Vec<ThemeOption> = BUILTIN_THEMES
+            .iter()
+            .map(|name| ThemeOption {
+                value: name.to_string(),
+                title: title(name),
+            })
+            .collect();
+        let mut css = String::new();
+        if let Some(path) = theme_file {
+            let user_theme = UserTheme::load(path)?;
+            options.push(ThemeOption {
+                value: format!("{} {}", user_theme.name, user_theme.base),
+                title: title(&user_theme.name),
+            });
+            css = user_theme.css();
+        }
+        let default = options
+            .iter()
+            .find(|option| option.value.split(' ').next() == Some(theme))
+            .ok_or_else(|| anyhow::anyhow!("unknown theme '{theme}'"))?
+            .value
+            .clone();
+        Ok(Self {
+            default,
+            options,
+            css,
+        })
+    }
+}
+
+impl UserTheme {
+    /// Loads a theme of the report or imports a `.tmTheme` or VS Code theme,
+    /// imported themes are named by their files
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let content = fs::read_to_string(path)?;
+        let theme = if content.trim_start().starts_with('<') {
+            textmate::import(theme_name(path), &content)?
+        } else {
+            let value: Value = serde_json::from_str(&strip_json_comments(&content))?;
+            if vscode::is_vscode_theme(&value) {
+                vscode::import(theme_name(path), &value)?
+            } else {
+                serde_json::from_value(value)?
+            }
+        };
+        theme.validate()?;
+        Ok(theme)
+    }
+
+    fn validate(&self) -> Result<(), anyhow::Error> {
+        let valid_name = !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_name || BUILTIN_THEMES.contains(&self.name.as_str()) {
+            return Err(anyhow::anyhow!("invalid theme name '{}'", self.name));
+        }
+        if !BUILTIN_THEMES.contains(&self.base.as_str()) {
+            return Err(anyhow::anyhow!("unknown base theme '{}'", self.base));
+        }
+        // keys and values go into the stylesheet of the report as they are
+        let mut keys = self.colors.keys().chain(self.classes.keys());
+        if let Some(key) = keys.find(|key| {
+            key.is_empty()
+                || !key
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        }) {
+            return Err(anyhow::anyhow!("invalid theme key '{key}'"));
+        }
+        let mut values = self
+            .colors
+            .values()
+            .map(String::as_str)
+            .chain(self.classes.values().flat_map(ClassStyle::values));
+        if let Some(value) = values.find(|value| value.contains([';', '{', '}', '<', '>'])) {
+            return Err(anyhow::anyhow!("invalid theme value '{value}'"));
+        }
+        Ok(())
+    }
+
+    pub fn css(&self) -> String {
+        let scope = format!(r#"[data-theme~="{}"]"#, self.name);
+        let variables = self
+            .colors
+            .iter()
+            .map(|(name, color)| format!("    --{name}: {color};\n"))
+            .collect::<String>();
+        let classes = self
+            .classes
+            .iter()
+            .map(|(class, style)| {
+                let selector = class
+                    .split(|c: char| c == '.' || c.is_whitespace())
+                    .filter(|part| !part.is_empty())
+                    .map(|part| format!(".{part}"))
+                    .collect::<String>();
+                format!("{scope} {selector} {{ {}}}\n", style.declarations())
+            })
+            .collect::<String>();
+        format!("{scope} {{\n{variables}}}\n{classes}")
+    }
+}
+
+impl ClassStyle {
+    /// Style of the `fontStyle` of TextMate, e.g. `bold italic`
+    fn new(color: Option<String>, font_style: Option<&str>) -> Self {
+        let font_style = font_style.unwrap_or_default();
+        match color {
+            Some(color) if font_style.trim().is_empty() => ClassStyle::Color(color),
+            color => ClassStyle::Style {
+                color,
+                background: None,
+                bold: font_style.contains("bold"),
+                italic: font_style.contains("italic"),
+                underline: font_style.contains("underline"),
+            },
+        }
+    }
+
+    fn values(&self) -> Vec<&str> {
+        match self {
+            ClassStyle::Color(color) => vec![color],
+            ClassStyle::Style {
+                color, background, ..
+            } => color.iter().chain(background).map(String::as_str).collect(),
+        }
+    }
+
+    fn declarations(&self) -> String {
+        match self {
+            ClassStyle::Color(color) => format!("color: {color}; "),
+            ClassStyle::Style {
+                color,
+                background,
+                bold,
+                italic,
+                underline,
+            } => {
+                let mut declarations = String::new();
+                if let Some(color) = color {
+                    declarations += &format!("color: {color}; ");
+                }
+                if let Some(background) = background {
+                    declarations += &format!("background-color: {background}; ");
+                }
+                if *bold {
+                    declarations += "font-weight: bold; ";
+                }
+                if *italic {
+                    declarations += "font-style: italic; ";
+                }
+                if *underline {
+                    declarations += "text-decoration: underline; ";
+                }
+                declarations
+            }
+        }
+    }
+}
+
+/// `Solarized Light.tmTheme` -> `solarized-light`
+fn theme_name(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map_or(String::new(), |stem| stem.to_string_lossy().to_lowercase());
+    stem.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Built-in theme with the background of the same brightness
+fn base_of_background(background: Option<&str>) -> String {
+    let channels = background
+        .and_then(|color| color.strip_prefix('#'))
+        .filter(|hex| hex.is_ascii())
+        .and_then(|hex| match hex.len() {
+            3 | 4 => hex
+                .chars()
+                .take(3)
+                .map(|c| u8::from_str_radix(&c.to_string(), 16).map(|value| value * 17))
+                .collect::<Result<Vec<_>, _>>()
+                .ok(),
+            6 | 8 => (0..3)
+                .map(|index| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16))
+                .collect::<Result<Vec<_>, _>>()
+                .ok(),
+            _ => None,
+        });
+    let light = channels.map_or(false, |channels| {
+        let luma =
+            0.299 * channels[0] as f32 + 0.587 * channels[1] as f32 + 0.114 * channels[2] as f32;
+        luma > 128.0
+    });
+    if light { "light" } else { "dark" }.to_string()
+}
+
+/// VS Code themes are JSON with comments and trailing commas
+fn strip_json_comments(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                result.push(c);
+                while let Some(c) = chars.next() {
+                    result.push(c);
+                    match c {
+                        '\\' => result.extend(chars.next()),
+                        '"' => break,
+                        _ => (),
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().map_or(false, |c| *c != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '}' | ']' => {
+                let trimmed = result.trim_end().len();
+                if result[..trimmed].ends_with(',') {
+                    result.truncate(trimmed - 1);
+                }
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// `high-contrast` -> `High contrast`
+fn title(name: &str) -> String {
+    let name = name.replace(['-', '_'], " ");
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_theme_css() {
+        let theme: UserTheme = serde_json::from_str(
+            r##"{
+                "name": "solarized",
+                "base": "light",
+                "colors": { "bg": "#FDF6E3" },
+                "classes": {
+                    "keyword": "#859900",
+                    "function.unsafe": { "color": "#DC322F", "bold": true }
+                }
+            }"##,
+        )
+        .unwrap();
+        theme.validate().unwrap();
+        assert_eq!(
+            theme.css(),
+            "[data-theme~=\"solarized\"] {\n    --bg: #FDF6E3;\n}\n\
+             [data-theme~=\"solarized\"] .function.unsafe { color: #DC322F; font-weight: bold; }\n\
+             [data-theme~=\"solarized\"] .keyword { color: #859900; }\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_json_comments() {
+        let content = r#"{
+            // comment
+            "a": "http://b", /* block */
+            "c": [1, 2,],
+        }"#;
+        let value: Value = serde_json::from_str(&strip_json_comments(content)).unwrap();
+        assert_eq!(value, serde_json::json!({ "a": "http://b", "c": [1, 2] }));
+    }
+
+    #[test]
+    fn test_base_of_background() {
+        assert_eq!(base_of_background(Some("#FDF6E3")), "light");
+        assert_eq!(base_of_background(Some("#fff")), "light");
+        assert_eq!(base_of_background(Some("#272822FF")), "dark");
+        assert_eq!(base_of_background(None), "dark");
+        assert_eq!(base_of_background(Some("#aé€")), "dark");
+        assert_eq!(
+            theme_name(Path::new("themes/Solarized (Light).tmTheme")),
+            "solarized-light"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let theme = |name: &str, base: &str| UserTheme {
+            name: name.to_string(),
+            base: base.to_string(),
+            colors: Default::default(),
+            classes: Default::default(),
+        };
+        assert!(theme("my-theme", "dark").validate().is_ok());
+        assert!(theme("light", "dark").validate().is_err());
+        assert!(theme("a b", "dark").validate().is_err());
+        assert!(theme("mine", "blue").validate().is_err());
+
+        let mut injected = theme("mine", "dark");
+        injected
+            .colors
+            .insert("bg".into(), "red}</style><script>".into());
+        assert!(injected.validate().is_err());
+        let mut injected = theme("mine", "dark");
+        injected.classes.insert(
+            "keyword".into(),
+            ClassStyle::Style {
+                color: None,
+                background: Some("red; color: blue".into()),
+                bold: false,
+                italic: false,
+                underline: false,
+            },
+        );
+        assert!(injected.validate().is_err());
+        let mut injected = theme("mine", "dark");
+        injected
+            .classes
+            .insert("a{}b".into(), ClassStyle::Color("red".into()));
+        assert!(injected.validate().is_err());
+        assert_eq!(title("high-contrast"), "High contrast");
+    }
+}
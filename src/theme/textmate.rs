@@ -0,0 +1,456 @@
+use super::{ClassStyle, UserTheme};
+use std::collections::BTreeMap;
+
+/// Scopes of the highlight classes, the most specific first
+const CLASS_SCOPES: &[(&str, &[&str])] = &[
+    ("comment", &["comment"]),
+    (
+        "documentation",
+        &[
+            "comment.block.documentation",
+            "comment.line.documentation",
+            "comment",
+        ],
+    ),
+    ("string_literal", &["string"]),
+    ("char_literal", &["constant.character", "string"]),
+    ("byte_literal", &["constant.character", "string"]),
+    ("numeric_literal", &["constant.numeric"]),
+    (
+        "bool_literal",
+        &["constant.language.boolean", "constant.language"],
+    ),
+    ("escape_sequence", &["constant.character.escape"]),
+    (
+        "format_specifier",
+        &["constant.other.placeholder", "constant.character.format"],
+    ),
+    ("keyword", &["keyword", "storage"]),
+    ("control", &["keyword.control"]),
+    ("operator", &["keyword.operator"]),
+    ("self_keyword", &["variable.language", "keyword"]),
+    ("function", &["entity.name.function", "support.function"]),
+    (
+        "macro",
+        &[
+            "entity.name.function.macro",
+            "support.function.macro",
+            "entity.name.function",
+        ],
+    ),
+    (
+        "struct",
+        &[
+            "entity.name.type.struct",
+            "entity.name.type",
+            "entity.name.class",
+        ],
+    ),
+    (
+        "enum",
+        &[
+            "entity.name.type.enum",
+            "entity.name.type",
+            "entity.name.class",
+        ],
+    ),
+    (
+        "union",
+        &[
+            "entity.name.type.union",
+            "entity.name.type",
+            "entity.name.class",
+        ],
+    ),
+    ("type", &["entity.name.type", "support.type"]),
+    (
+        "type_alias",
+        &["entity.name.type.alias", "entity.name.type"],
+    ),
+    (
+        "trait",
+        &[
+            "entity.name.type.trait",
+            "entity.other.inherited-class",
+            "entity.name.type",
+        ],
+    ),
+    (
+        "builtin_type",
+        &["support.type.primitive", "support.type", "storage.type"],
+    ),
+    (
+        "type_param",
+        &[
+            "entity.name.type.parameter",
+            "entity.name.type",
+            "variable.parameter",
+        ],
+    ),
+    (
+        "lifetime",
+        &[
+            "entity.name.type.lifetime",
+            "storage.modifier.lifetime",
+            "storage.modifier",
+        ],
+    ),
+    (
+        "module",
+        &[
+            "entity.name.namespace",
+            "entity.name.module",
+            "entity.name.type",
+        ],
+    ),
+    ("variable", &["variable.other", "variable"]),
+    ("value_param", &["variable.parameter", "variable"]),
+    (
+        "field",
+        &[
+            "variable.other.property",
+            "variable.other.member",
+            "variable.other",
+        ],
+    ),
+    (
+        "enum_variant",
+        &[
+            "variable.other.enummember",
+            "entity.name.enum",
+            "constant.other",
+        ],
+    ),
+    (
+        "const",
+        &["variable.other.constant", "constant.other", "constant"],
+    ),
+    (
+        "static",
+        &["variable.other.constant", "constant.other", "constant"],
+    ),
+    (
+        "attribute",
+        &["meta.attribute", "entity.other.attribute-name"],
+    ),
+    ("derive", &["meta.attribute", "entity.other.attribute-name"]),
+    ("label", &["entity.name.label", "keyword.control"]),
+    ("punctuation", &["punctuation"]),
+];
+
+/// Style of the scopes of a theme
+#[derive(Debug, Default, Clone)]
+pub struct ScopeRule {
+    /// Last scopes of the selectors, `source.rust string` is matched as `string`
+    pub scopes: Vec<String>,
+    pub foreground: Option<String>,
+    pub font_style: Option<String>,
+}
+
+impl ScopeRule {
+    pub fn new(selector: &str, foreground: Option<String>, font_style: Option<String>) -> Self {
+        let scopes = selector
+            .split(',')
+            // exclusions can't be expressed by the classes
+            .filter(|selector| !selector.trim_start().starts_with('-') && !selector.contains(" -"))
+            .filter_map(|selector| selector.split_whitespace().last())
+            .map(str::to_string)
+            .collect();
+        Self {
+            scopes,
+            foreground,
+            font_style,
+        }
+    }
+
+    fn matches(&self, scope: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .filter(|selector| {
+                scope
+                    .strip_prefix(selector.as_str())
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .map(String::len)
+            .max()
+    }
+}
+
+/// Styles of the highlight classes by the rules which match their scopes best
+pub fn classes_from_rules(rules: &[ScopeRule]) -> BTreeMap<String, ClassStyle> {
+    let rules: Vec<&ScopeRule> = rules
+        .iter()
+        .filter(|rule| rule.foreground.is_some() || rule.font_style.is_some())
+        .collect();
+    CLASS_SCOPES
+        .iter()
+        .filter_map(|(class, scopes)| {
+            // the longest selector wins, the later one of equal selectors
+            let rule = scopes.iter().find_map(|scope| {
+                rules
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, rule)| Some(((rule.matches(scope)?, index), rule)))
+                    .max_by_key(|(key, _)| *key)
+                    .map(|(_, rule)| rule)
+            })?;
+            let style = ClassStyle::new(rule.foreground.clone(), rule.font_style.as_deref());
+            Some((class.to_string(), style))
+        })
+        .collect()
+}
+
+/// Converts a TextMate or Sublime `.tmTheme`
+pub fn import(name: String, content: &str) -> Result<UserTheme, anyhow::Error> {
+    let plist = Parser { rest: content }.document()?;
+    let entries = match plist.get("settings") {
+        Some(Plist::Array(entries)) => entries,
+        _ => return Err(anyhow::anyhow!("tmTheme has no settings")),
+    };
+    let mut colors = BTreeMap::new();
+    let mut rules = vec![];
+    for entry in entries {
+        let settings = entry.get("settings");
+        let setting = |key: &str| settings.and_then(|settings| settings.string(key));
+        match entry.string("scope") {
+            Some(scope) => rules.push(ScopeRule::new(
+                &scope,
+                setting("foreground"),
+                setting("fontStyle"),
+            )),
+            None => {
+                for (key, variables) in GLOBAL_SETTINGS {
+                    if let Some(color) = setting(key) {
+                        for variable in *variables {
+                            colors.insert(variable.to_string(), color.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let base = super::base_of_background(colors.get("bg").map(String::as_str));
+    Ok(UserTheme {
+        name,
+        base,
+        colors,
+        classes: classes_from_rules(&rules),
+    })
+}
+
+/// Palette variables set by the global settings of tmTheme
+const GLOBAL_SETTINGS: &[(&str, &[&str])] = &[
+    ("background", &["bg", "page-bg", "menu-bg"]),
+    ("foreground", &["fg", "tree-fg", "menu-fg"]),
+    ("lineHighlight", &["bg-light"]),
+    ("selection", &["line-selected"]),
+    ("gutterForeground", &["line-number"]),
+];
+
+/// Values of a property list, only strings are kept
+#[derive(Debug, PartialEq)]
+enum Plist {
+    Dict(Vec<(String, Plist)>),
+    Array(Vec<Plist>),
+    String(String),
+    Other,
+}
+
+impl Plist {
+    fn get(&self, key: &str) -> Option<&Plist> {
+        match self {
+            Plist::Dict(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        match self.get(key)? {
+            Plist::String(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Parser of the xml property lists of tmTheme
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn document(&mut self) -> Result<Plist, anyhow::Error> {
+        loop {
+            let tag = self.tag()?;
+            if tag.starts_with("plist") {
+                continue;
+            }
+            return self.value(tag);
+        }
+    }
+
+    /// Content of the next tag, skipping declarations and comments
+    fn tag(&mut self) -> Result<&'a str, anyhow::Error> {
+        loop {
+            let start = self
+                .rest
+                .find('<')
+                .ok_or_else(|| anyhow::anyhow!("unexpected end of tmTheme"))?;
+            self.rest = &self.rest[start..];
+            let end = if self.rest.starts_with("<!--") {
+                self.rest.find("-->").map(|end| end + 3)
+            } else {
+                self.rest.find('>').map(|end| end + 1)
+            }
+            .ok_or_else(|| anyhow::anyhow!("unclosed tag in tmTheme"))?;
+            let tag = &self.rest[1..end - 1];
+            self.rest = &self.rest[end..];
+            if !tag.starts_with('?') && !tag.starts_with('!') {
+                return Ok(tag.trim());
+            }
+        }
+    }
+
+    fn text_until(&mut self, close: &str) -> Result<String, anyhow::Error> {
+        let end = self
+            .rest
+            .find(close)
+            .ok_or_else(|| anyhow::anyhow!("missing {close} in tmTheme"))?;
+        let text = unescape(&self.rest[..end]);
+        self.rest = &self.rest[end + close.len()..];
+        Ok(text)
+    }
+
+    fn value(&mut self, tag: &str) -> Result<Plist, anyhow::Error> {
+        Ok(match tag {
+            "dict" => {
+                let mut entries = vec![];
+                loop {
+                    match self.tag()? {
+                        "/dict" => break Plist::Dict(entries),
+                        "key" => {
+                            let key = self.text_until("</key>")?;
+                            let tag = self.tag()?;
+                            entries.push((key, self.value(tag)?));
+                        }
+                        tag => return Err(anyhow::anyhow!("unexpected <{tag}> in dict")),
+                    }
+                }
+            }
+            "array" => {
+                let mut values = vec![];
+                loop {
+                    match self.tag()? {
+                        "/array" => break Plist::Array(values),
+                        tag => values.push(self.value(tag)?),
+                    }
+                }
+            }
+            "string" => Plist::String(self.text_until("</string>")?),
+            "string/" => Plist::String(String::new()),
+            tag if tag.ends_with('/') => Plist::Other,
+            tag => {
+                self.text_until(&format!("</{tag}>"))?;
+                Plist::Other
+            }
+        })
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_tm_theme() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>name</key>
+    <string>Sample &amp; Co</string>
+    <key>settings</key>
+    <array>
+        <dict>
+            <key>settings</key>
+            <dict>
+                <key>background</key>
+                <string>#FDF6E3</string>
+                <key>foreground</key>
+                <string>#657B83</string>
+            </dict>
+        </dict>
+        <!-- comments are skipped -->
+        <dict>
+            <key>scope</key>
+            <string>comment, string.quoted</string>
+            <key>settings</key>
+            <dict>
+                <key>fontStyle</key>
+                <string>italic</string>
+                <key>foreground</key>
+                <string>#93A1A1</string>
+            </dict>
+        </dict>
+        <dict>
+            <key>scope</key>
+            <string>keyword</string>
+            <key>settings</key>
+            <dict>
+                <key>foreground</key>
+                <string>#859900</string>
+            </dict>
+        </dict>
+        <dict>
+            <key>scope</key>
+            <string>source.rust keyword.control</string>
+            <key>settings</key>
+            <dict>
+                <key>foreground</key>
+                <string>#CB4B16</string>
+                <key>fontStyle</key>
+                <string/>
+            </dict>
+        </dict>
+    </array>
+    <key>uuid</key>
+    <string>0</string>
+    <key>semanticClass</key>
+    <true/>
+</dict>
+</plist>"#;
+        let theme = import("sample".to_string(), content).unwrap();
+        assert_eq!(theme.base, "light");
+        assert_eq!(theme.colors["bg"], "#FDF6E3");
+        assert_eq!(theme.colors["fg"], "#657B83");
+        assert_eq!(
+            theme.classes["comment"].declarations(),
+            "color: #93A1A1; font-style: italic; "
+        );
+        assert!(theme.classes.get("string_literal").is_none());
+        assert_eq!(theme.classes["keyword"].declarations(), "color: #859900; ");
+        assert_eq!(theme.classes["control"].declarations(), "color: #CB4B16; ");
+        assert_eq!(theme.classes["label"].declarations(), "color: #CB4B16; ");
+        assert!(theme.classes.get("function").is_none());
+    }
+
+    #[test]
+    fn test_rule_matches() {
+        let rule = ScopeRule::new("entity.name, source.rust support.type", None, None);
+        assert_eq!(rule.matches("entity.name.function"), Some(11));
+        assert_eq!(rule.matches("support.type"), Some(12));
+        assert_eq!(rule.matches("entity.names"), None);
+        let rule = ScopeRule::new("string -string.quoted", None, None);
+        assert!(rule.scopes.is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use super::{
+    textmate::{classes_from_rules, ScopeRule},
+    ClassStyle, UserTheme,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Semantic token types of rust-analyzer and their highlight classes
+const SEMANTIC_TYPES: &[(&str, &str)] = &[
+    ("attribute", "attribute"),
+    ("boolean", "bool_literal"),
+    ("builtinType", "builtin_type"),
+    ("comment", "comment"),
+    ("constParameter", "const_param"),
+    ("derive", "derive"),
+    ("enum", "enum"),
+    ("enumMember", "enum_variant"),
+    ("escapeSequence", "escape_sequence"),
+    ("formatSpecifier", "format_specifier"),
+    ("function", "function"),
+    ("interface", "trait"),
+    ("keyword", "keyword"),
+    ("label", "label"),
+    ("lifetime", "lifetime"),
+    ("macro", "macro"),
+    ("method", "function"),
+    ("namespace", "module"),
+    ("number", "numeric_literal"),
+    ("operator", "operator"),
+    ("parameter", "value_param"),
+    ("property", "field"),
+    ("punctuation", "punctuation"),
+    ("selfKeyword", "self_keyword"),
+    ("string", "string_literal"),
+    ("struct", "struct"),
+    ("type", "type"),
+    ("typeAlias", "type_alias"),
+    ("typeParameter", "type_param"),
+    ("union", "union"),
+    ("unresolvedReference", "unresolved_reference"),
+    ("variable", "variable"),
+];
+
+/// Semantic token modifiers and their highlight classes
+const SEMANTIC_MODIFIERS: &[(&str, &str)] = &[
+    ("associated", "associated"),
+    ("async", "async"),
+    ("attribute", "attribute"),
+    ("callable", "callable"),
+    ("consuming", "consuming"),
+    ("controlFlow", "control"),
+    ("declaration", "definition"),
+    ("defaultLibrary", "default_library"),
+    ("documentation", "documentation"),
+    ("injected", "injected"),
+    ("intraDocLink", "intra_doc_link"),
+    ("library", "library"),
+    ("mutable", "mutable"),
+    ("public", "public"),
+    ("reference", "reference"),
+    ("static", "static"),
+    ("trait", "trait"),
+    ("unsafe", "unsafe"),
+];
+
+/// Colors of the workbench and the palette variables they set
+const WORKBENCH_COLORS: &[(&str, &[&str])] = &[
+    ("editor.background", &["bg", "page-bg"]),
+    ("editor.foreground", &["fg"]),
+    ("editor.lineHighlightBackground", &["bg-light"]),
+    ("editor.selectionBackground", &["line-selected"]),
+    ("editorLineNumber.foreground", &["line-number"]),
+    ("editorGroupHeader.tabsBackground", &["bg-panel"]),
+    ("sideBar.background", &["page-bg"]),
+    ("sideBar.foreground", &["tree-fg"]),
+    ("list.highlightForeground", &["tree-selected"]),
+    ("editorWidget.background", &["menu-bg"]),
+    ("editorWidget.foreground", &["menu-fg"]),
+    ("textLink.foreground", &["link"]),
+    ("focusBorder", &["accent"]),
+];
+
+pub fn is_vscode_theme(theme: &Value) -> bool {
+    theme.get("tokenColors").is_some() || theme.get("semanticTokenColors").is_some()
+}
+
+/// Converts a VS Code color theme: token colors by TextMate scopes,
+/// semantic token colors and the colors of the editor
+pub fn import(name: String, theme: &Value) -> Result<UserTheme, anyhow::Error> {
+    let mut colors = BTreeMap::new();
+    if let Some(Value::Object(workbench)) = theme.get("colors") {
+        // the more specific colors follow the general ones
+        for (key, variables) in WORKBENCH_COLORS {
+            if let Some(color) = workbench.get(*key).and_then(Value::as_str) {
+                for variable in *variables {
+                    colors.insert(variable.to_string(), color.to_string());
+                }
+            }
+        }
+    }
+    let rules: Vec<ScopeRule> = match theme.get("tokenColors") {
+        Some(Value::Array(rules)) => rules.iter().filter_map(scope_rule).collect(),
+        _ => vec![],
+    };
+    let mut classes = classes_from_rules(&rules);
+    if let Some(Value::Object(semantic)) = theme.get("semanticTokenColors") {
+        for (selector, style) in semantic {
+            if let (Some(class), Some(style)) = (semantic_class(selector), semantic_style(style)) {
+                classes.insert(class, style);
+            }
+        }
+    }
+    let base = match theme.get("type").and_then(Value::as_str) {
+        Some("light") => "light".to_string(),
+        Some("dark") => "dark".to_string(),
+        Some(kind) if kind.starts_with("hc") => "high-contrast".to_string(),
+        _ => super::base_of_background(colors.get("bg").map(String::as_str)),
+    };
+    Ok(UserTheme {
+        name,
+        base,
+        colors,
+        classes,
+    })
+}
+
+fn scope_rule(rule: &Value) -> Option<ScopeRule> {
+    let selector = match rule.get("scope")? {
+        Value::String(scope) => scope.clone(),
+        Value::Array(scopes) => scopes
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => return None,
+    };
+    let settings = rule.get("settings")?;
+    let setting = |key: &str| {
+        settings
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    Some(ScopeRule::new(
+        &selector,
+        setting("foreground"),
+        setting("fontStyle"),
+    ))
+}
+
+/// `variable.mutable:rust` -> `variable.mutable`, `*.unsafe` -> `unsafe`
+fn semantic_class(selector: &str) -> Option<String> {
+    let selector = match selector.split_once(':') {
+        Some((selector, "rust")) => selector,
+        Some(_) => return None,
+        None => selector,
+    };
+    let mut parts = selector.split('.');
+    let mut classes = vec![];
+    match parts.next()? {
+        "*" => (),
+        kind => classes.push(lookup(SEMANTIC_TYPES, kind)?),
+    }
+    for modifier in parts {
+        classes.push(lookup(SEMANTIC_MODIFIERS, modifier)?);
+    }
+    (!classes.is_empty()).then(|| classes.join("."))
+}
+
+fn lookup(table: &[(&str, &'static str)], name: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, class)| *class)
+}
+
+fn semantic_style(style: &Value) -> Option<ClassStyle> {
+    match style {
+        Value::String(color) => Some(ClassStyle::Color(color.clone())),
+        Value::Object(style) => {
+            let flag = |key: &str| style.get(key).and_then(Value::as_bool).unwrap_or(false);
+            let font_style = style.get("fontStyle").and_then(Value::as_str).unwrap_or("");
+            Some(ClassStyle::Style {
+                color: style
+                    .get("foreground")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                background: None,
+                bold: flag("bold") || font_style.contains("bold"),
+                italic: flag("italic") || font_style.contains("italic"),
+                underline: flag("underline") || font_style.contains("underline"),
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_vscode_theme() {
+        let theme = serde_json::json!({
+            "name": "Sample",
+            "type": "light",
+            "colors": {
+                "editor.background": "#FFFFFF",
+                "editor.foreground": "#333333",
+                "sideBar.background": "#F3F3F3"
+            },
+            "tokenColors": [
+                { "settings": { "foreground": "#000000" } },
+                { "scope": ["comment", "punctuation.definition.comment"], "settings": { "foreground": "#008000", "fontStyle": "italic" } },
+                { "scope": "entity.name.function", "settings": { "foreground": "#795E26" } }
+            ],
+            "semanticTokenColors": {
+                "variable.mutable": { "underline": true },
+                "*.unsafe:rust": "#CD3131",
+                "function:python": "#000000",
+                "newType": "#000000"
+            }
+        });
+        assert!(is_vscode_theme(&theme));
+        let theme = import("sample".to_string(), &theme).unwrap();
+        assert_eq!(theme.base, "light");
+        assert_eq!(theme.colors["bg"], "#FFFFFF");
+        assert_eq!(theme.colors["page-bg"], "#F3F3F3");
+        assert_eq!(theme.colors["fg"], "#333333");
+        assert_eq!(
+            theme.classes["comment"].declarations(),
+            "color: #008000; font-style: italic; "
+        );
+        assert_eq!(theme.classes["function"].declarations(), "color: #795E26; ");
+        assert_eq!(theme.classes["macro"].declarations(), "color: #795E26; ");
+        assert_eq!(
+            theme.classes["variable.mutable"].declarations(),
+            "text-decoration: underline; "
+        );
+        assert_eq!(theme.classes["unsafe"].declarations(), "color: #CD3131; ");
+        assert_eq!(theme.classes.len(), 6);
+    }
+}